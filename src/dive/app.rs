@@ -1,23 +1,43 @@
 use crate::dive::bookmark_manager::BookmarkManager;
+use crate::dive::cache::HttpCache;
 use crate::dive::command_queue::{Command, CommandQueue};
+use crate::dive::completion::{Suggestion, SuggestionSource};
+use crate::dive::cookies::CookieJar;
+use crate::dive::fetcher::http::UreqFetcher;
 use crate::dive::gosub_logger::LogPool;
-use crate::dive::tab_manager::TabManager;
+use crate::dive::history_manager::HistoryManager;
+use crate::dive::loader::LoadContext;
+use crate::dive::search::{PageSearch, SearchOptions};
+use crate::dive::session::Session;
+use crate::dive::settings::Settings;
+use crate::dive::tab_manager::{Tab, TabManager};
+use crate::dive::url_input::resolve_url_input;
 use crate::dive::widget_manager::{Widget, WidgetManager};
 use crate::dive::widgets::bookmark_list::BookmarkListWidget;
+use crate::dive::widgets::cookie_list::CookieListWidget;
 use crate::dive::widgets::help::Help;
+use crate::dive::widgets::history_list::HistoryListWidget;
 use crate::dive::widgets::input::{InputSubmitCommand, InputWidget};
 use crate::dive::widgets::log::LogWidget;
 use crate::dive::widgets::menu_bar::MenuBar;
+use crate::dive::widgets::page_info::PageInfoWidget;
+use crate::dive::widgets::search::SearchWidget;
 use crate::dive::widgets::status_bar::StatusBar;
 use crate::dive::widgets::tab_list::TabListWidget;
 use crate::dive::widgets::tabs::TabsWidget;
+use crate::dive::widgets::url_input::UrlInputWidget;
+use crate::dive::widgets::workspace_list::WorkspaceListWidget;
+use crate::dive::workspace_manager::{WorkspaceManager, DEFAULT_WORKSPACE};
+use chrono::{Local, TimeZone, Utc};
 use crossterm::event;
 use crossterm::event::Event::Key;
 use crossterm::event::KeyCode::Char;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 pub struct App {
     pub should_quit: bool,
@@ -26,40 +46,79 @@ pub struct App {
     pub menu_bar: Rc<RefCell<MenuBar>>,
     pub tab_manager: Rc<RefCell<TabManager>>,
     pub bookmark_manager: Rc<RefCell<BookmarkManager>>,
+    pub history_manager: Rc<RefCell<HistoryManager>>,
+    pub workspace_manager: Rc<RefCell<WorkspaceManager>>,
 
     pub widget_manager: WidgetManager,
     pub command_queue: CommandQueue,
     pub log_pool: Arc<Mutex<LogPool>>,
+
+    /// File the open tabs are saved to. Tabs are not saved when not set.
+    session_path: Option<PathBuf>,
+    /// Session as it was last saved, so unchanged sessions are not written over and over
+    last_session: Option<Session>,
+    last_autosave: Instant,
 }
 
 impl App {
     pub fn new(pool: Arc<Mutex<LogPool>>) -> Self {
         let bm = BookmarkManager::new_from_file("bookmarks.json");
+        let settings = Settings::new_from_file("settings.json");
+        let command_queue = CommandQueue::new();
+        let cookies = match CookieJar::default_path() {
+            Some(path) => CookieJar::new_from_file(path),
+            None => CookieJar::in_memory(),
+        };
+        let cache = match HttpCache::default_dir() {
+            Some(dir) => HttpCache::new_from_dir(dir, settings.max_cache_size),
+            None => HttpCache::disabled(),
+        };
+        let context = LoadContext {
+            fetcher: Arc::new(UreqFetcher::new(&settings)),
+            settings,
+            cookies: Arc::new(Mutex::new(cookies)),
+            cache: Arc::new(Mutex::new(cache)),
+        };
+        let tab_manager = TabManager::new(command_queue.sender(), context);
+        let history = match HistoryManager::default_path() {
+            Some(path) => HistoryManager::new_from_file(path),
+            None => HistoryManager::empty(),
+        };
 
         let mut app = Self {
             should_quit: false,
 
             status_bar: Rc::new(RefCell::new(StatusBar::new())),
             menu_bar: Rc::new(RefCell::new(MenuBar::new())),
-            tab_manager: Rc::new(RefCell::new(TabManager::new())),
+            tab_manager: Rc::new(RefCell::new(tab_manager)),
             bookmark_manager: Rc::new(RefCell::new(bm)),
+            history_manager: Rc::new(RefCell::new(history)),
+            workspace_manager: Rc::new(RefCell::new(WorkspaceManager::new(DEFAULT_WORKSPACE))),
 
             widget_manager: WidgetManager::new(),
-            command_queue: CommandQueue::new(),
+            command_queue,
 
             log_pool: pool.clone(),
+
+            session_path: Session::default_path(),
+            last_session: None,
+            last_autosave: Instant::now(),
         };
 
         // Add the main widgets
-        let w1 = Widget::new("statusbar", 0, true, app.status_bar.clone());
+        let w1 = Widget::new("statusbar", true, app.status_bar.clone());
         app.widget_manager.create(w1);
-        let w1 = Widget::new("menubar", 0, true, app.menu_bar.clone());
+        let w1 = Widget::new("menubar", true, app.menu_bar.clone());
         app.widget_manager.create(w1);
 
-        let inner = TabsWidget::new(app.tab_manager.clone());
-        let w1 = Widget::new("tabs", 0, true, Rc::new(RefCell::new(inner)));
+        let inner = TabsWidget::new(app.tab_manager.clone(), app.status_bar.clone());
+        let w1 = Widget::new("tabs", true, Rc::new(RefCell::new(inner)));
         app.widget_manager.create(w1);
 
+        app.status_bar
+            .borrow_mut()
+            .workspace(Some(DEFAULT_WORKSPACE.into()));
+
         app
     }
 
@@ -84,6 +143,19 @@ impl App {
                 }
             }
 
+            // Keys that are not handled by a focussed widget can be used by the page itself
+            if handle_as_unfocussed {
+                if let Some(widget) = self.widget_manager.find("tabs") {
+                    if let Ok(Some(_)) = widget
+                        .inner
+                        .borrow_mut()
+                        .event_handler(&mut self.command_queue, key)
+                    {
+                        handle_as_unfocussed = false;
+                    }
+                }
+            }
+
             if handle_as_unfocussed {
                 self.process_key(key)?;
             }
@@ -113,7 +185,7 @@ impl App {
             KeyCode::F(1) => {
                 // Add some test widgets
                 let inner = Help::new();
-                let w1 = Widget::new("help", 255, false, Rc::new(RefCell::new(inner)));
+                let w1 = Widget::new("help", false, Rc::new(RefCell::new(inner)));
                 self.widget_manager.create(w1);
 
                 self.command_queue.push(Command::ShowWidget {
@@ -124,27 +196,59 @@ impl App {
             // Show tab list
             KeyCode::F(2) => {
                 let inner = TabListWidget::new(self.tab_manager.clone());
-                let widget = Widget::new("tab_list", 64, false, Rc::new(RefCell::new(inner)));
+                let widget = Widget::new("tab_list", false, Rc::new(RefCell::new(inner)));
                 self.widget_manager.create(widget);
                 self.command_queue.push(Command::ShowWidget {
                     id: "tab_list".into(),
                     focus: true,
                 });
             }
+            // Show cookie manager
+            KeyCode::F(3) => {
+                let cookies = self.tab_manager.borrow().context.cookies.clone();
+                let inner = CookieListWidget::new(cookies);
+                let widget = Widget::new("cookie_list", false, Rc::new(RefCell::new(inner)));
+                self.widget_manager.create(widget);
+                self.command_queue.push(Command::ShowWidget {
+                    id: "cookie_list".into(),
+                    focus: true,
+                });
+            }
+            // Show page info
+            KeyCode::F(4) => {
+                let inner = PageInfoWidget::new(self.tab_manager.clone());
+                let widget = Widget::new("page_info", false, Rc::new(RefCell::new(inner)));
+                self.widget_manager.create(widget);
+                self.command_queue.push(Command::ShowWidget {
+                    id: "page_info".into(),
+                    focus: true,
+                });
+            }
             // Show logs
             KeyCode::F(6) => {
                 let inner = LogWidget::new(self.log_pool.clone());
-                let widget = Widget::new("logs", 64, false, Rc::new(RefCell::new(inner)));
+                let widget = Widget::new("logs", false, Rc::new(RefCell::new(inner)));
                 self.widget_manager.create(widget);
                 self.command_queue.push(Command::ShowWidget {
                     id: "logs".into(),
                     focus: true,
                 });
             }
+            // Show history
+            KeyCode::F(7) => {
+                let tab_idx = self.tab_manager.borrow().current;
+                let inner = HistoryListWidget::new(self.history_manager.clone(), tab_idx);
+                let widget = Widget::new("history_list", false, Rc::new(RefCell::new(inner)));
+                self.widget_manager.create(widget);
+                self.command_queue.push(Command::ShowWidget {
+                    id: "history_list".into(),
+                    focus: true,
+                });
+            }
             // Show bookmark list
             KeyCode::F(8) => {
                 let inner = BookmarkListWidget::new(self.bookmark_manager.clone());
-                let widget = Widget::new("bookmark_list", 64, false, Rc::new(RefCell::new(inner)));
+                let widget = Widget::new("bookmark_list", false, Rc::new(RefCell::new(inner)));
                 self.widget_manager.create(widget);
                 self.command_queue.push(Command::ShowWidget {
                     id: "bookmark_list".into(),
@@ -152,6 +256,19 @@ impl App {
                 });
             }
             // KeyCode::F(9) => self.menu_active = !self.menu_active,
+            // Show workspaces
+            Char('w') if key.modifiers.contains(KeyModifiers::ALT) => {
+                let inner = WorkspaceListWidget::new(
+                    self.workspace_manager.clone(),
+                    self.tab_manager.clone(),
+                );
+                let widget = Widget::new("workspace_list", false, Rc::new(RefCell::new(inner)));
+                self.widget_manager.create(widget);
+                self.command_queue.push(Command::ShowWidget {
+                    id: "workspace_list".into(),
+                    focus: true,
+                });
+            }
             // Switch to previous tab
             KeyCode::Tab if key.modifiers.contains(KeyModifiers::SHIFT) => {
                 let idx = self.tab_manager.borrow_mut().prev();
@@ -174,23 +291,64 @@ impl App {
             }
             // Asks and opens URL in new tab
             Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                let name = "";
-                let inner = InputWidget::new(
+                let inner = UrlInputWidget::new(
                     "Enter the URL to visit",
-                    name,
-                    80,
+                    "",
                     InputSubmitCommand::OpenTabWithUrl,
+                    self.url_suggestions(),
+                );
+
+                let widget = Widget::new("input", false, Rc::new(RefCell::new(inner)));
+                self.widget_manager.create(widget);
+                self.command_queue.push(Command::ShowWidget {
+                    id: "input".into(),
+                    focus: true,
+                });
+            }
+            // Asks and opens URL in the current tab
+            Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let url = self.tab_manager.borrow().current().url.clone();
+
+                let tab_idx = self.tab_manager.borrow().current;
+                let inner = UrlInputWidget::new(
+                    "Enter the URL to visit",
+                    &url,
+                    InputSubmitCommand::NavigateTabToUrl { tab_idx },
+                    self.url_suggestions(),
                 );
 
-                let widget = Widget::new("input", 64, false, Rc::new(RefCell::new(inner)));
+                let widget = Widget::new("input", false, Rc::new(RefCell::new(inner)));
                 self.widget_manager.create(widget);
                 self.command_queue.push(Command::ShowWidget {
                     id: "input".into(),
                     focus: true,
                 });
             }
+            // Find text in the current page
+            Char('/')
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.open_search()
+            }
+            Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => self.open_search(),
+            // Browse back to the previous page in the current tab
+            Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let idx = self.tab_manager.borrow().current;
+                self.command_queue.push(Command::NavigateBack { idx });
+            }
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::ALT) => {
+                let idx = self.tab_manager.borrow().current;
+                self.command_queue.push(Command::NavigateBack { idx });
+            }
+            // Browse forward to the next page in the current tab
+            KeyCode::Right if key.modifiers.contains(KeyModifiers::ALT) => {
+                let idx = self.tab_manager.borrow().current;
+                self.command_queue.push(Command::NavigateForward { idx });
+            }
             // change the name of the current tab
-            Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 let name = self.tab_manager.borrow().current().name.clone();
 
                 let tab_idx = self.tab_manager.borrow().current;
@@ -201,7 +359,7 @@ impl App {
                     InputSubmitCommand::RenameTab { tab_idx },
                 );
 
-                let widget = Widget::new("input", 64, false, Rc::new(RefCell::new(inner)));
+                let widget = Widget::new("input", false, Rc::new(RefCell::new(inner)));
                 self.widget_manager.create(widget);
                 self.command_queue.push(Command::ShowWidget {
                     id: "input".into(),
@@ -220,6 +378,41 @@ impl App {
                     url: "gosub://blank".into(),
                 });
             }
+            // Open the source of the current page in a new tab
+            Char('u') if key.modifiers.contains(KeyModifiers::ALT) => {
+                let url = self.tab_manager.borrow().current().url.clone();
+                if !url.starts_with("view-source:") {
+                    self.command_queue.push(Command::NewTabUrl {
+                        title: "Source".into(),
+                        url: format!("view-source:{}", url),
+                    });
+                }
+            }
+            // Stop loading the current tab
+            KeyCode::Esc => {
+                let idx = self.tab_manager.borrow().current;
+                self.command_queue.push(Command::CancelLoad { idx });
+            }
+            // Reload the current tab
+            KeyCode::F(5) => {
+                let idx = self.tab_manager.borrow().current;
+                self.command_queue
+                    .push(Command::Reload { idx, hard: false });
+            }
+            Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let idx = self.tab_manager.borrow().current;
+                self.command_queue
+                    .push(Command::Reload { idx, hard: false });
+            }
+            // Reload the current tab, bypassing any caches
+            Char('r') if key.modifiers.contains(KeyModifiers::ALT) => {
+                let idx = self.tab_manager.borrow().current;
+                self.command_queue.push(Command::Reload { idx, hard: true });
+            }
+            // Throw away everything in the HTTP cache
+            Char('c') if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.command_queue.push(Command::ClearCache);
+            }
             // quit application
             Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.command_queue.push(Command::Quit);
@@ -229,11 +422,177 @@ impl App {
         Ok(())
     }
 
+    /// Reopens the tabs of the previous session, unless disabled in the settings. Returns true
+    /// when any tab has been restored.
+    pub fn restore_session(&mut self) -> bool {
+        if !self.tab_manager.borrow().context.settings.restore_session {
+            return false;
+        }
+        let Some(session) = self.session_path.as_deref().and_then(Session::load) else {
+            return false;
+        };
+
+        let restored = self
+            .workspace_manager
+            .borrow_mut()
+            .restore(&session, &mut self.tab_manager.borrow_mut());
+        let Some(restored) = restored else {
+            return false;
+        };
+        if self.tab_manager.borrow().len() == 0 {
+            self.tab_manager
+                .borrow_mut()
+                .open("New Tab", "gosub://blank");
+        }
+
+        let status = if session.clean_exit {
+            format!("Restored {} tabs", restored)
+        } else {
+            let saved = Local
+                .timestamp_opt(session.saved, 0)
+                .single()
+                .unwrap_or_default();
+            format!(
+                "Dive did not quit properly, recovered {} tabs from the autosave at {}",
+                restored,
+                saved.format("%H:%M")
+            )
+        };
+        self.status_bar.borrow_mut().status(&status);
+        self.update_workspace_status();
+
+        true
+    }
+
+    /// Shows the active workspace and its current tab in the status bar
+    fn update_workspace_status(&mut self) {
+        let name = self.workspace_manager.borrow().active_name().to_string();
+        self.status_bar.borrow_mut().workspace(Some(name));
+        self.status_bar
+            .borrow_mut()
+            .tab_info(Some(self.tab_manager.borrow().current().info()));
+    }
+
+    /// Saves the open tabs and the browsing history every autosave_interval seconds, so they
+    /// can be recovered after a crash. Nothing is written when they have not changed since the
+    /// last save.
+    pub fn autosave(&mut self) {
+        let interval = self.tab_manager.borrow().context.settings.autosave_interval;
+        if interval == 0 || self.last_autosave.elapsed() < Duration::from_secs(interval) {
+            return;
+        }
+        self.last_autosave = Instant::now();
+        self.save_session(false);
+        self.history_manager.borrow_mut().save();
+    }
+
+    /// Saves the open tabs to the session file. A clean exit marks the session as saved on quit.
+    fn save_session(&mut self, clean_exit: bool) {
+        let Some(path) = &self.session_path else {
+            return;
+        };
+
+        let mut session = self
+            .workspace_manager
+            .borrow()
+            .session(&self.tab_manager.borrow());
+        session.clean_exit = clean_exit;
+        if !clean_exit && self.last_session.as_ref() == Some(&session) {
+            return;
+        }
+
+        let mut saved = session.clone();
+        saved.saved = Utc::now().timestamp();
+        if let Err(e) = saved.save(path) {
+            log::error!("Unable to save session to {}: {}", path.display(), e);
+            return;
+        }
+        self.last_session = Some(session);
+    }
+
+    /// Adds the page that has just been loaded in the given tab to the browsing history. Our
+    /// own gosub: pages and page sources are left out.
+    fn record_visit(&self, tab: &Tab) {
+        if tab.url.starts_with("gosub:") || tab.url.starts_with("view-source:") {
+            return;
+        }
+
+        let title = tab.page_title().unwrap_or_default();
+        self.history_manager
+            .borrow_mut()
+            .record_visit(&tab.url, &title);
+    }
+
+    /// Returns the pages that can be suggested in the URL prompt
+    fn url_suggestions(&self) -> Vec<Suggestion> {
+        let mut suggestions = vec![];
+
+        for bookmark in self.bookmark_manager.borrow().bookmarks() {
+            suggestions.push(Suggestion {
+                url: bookmark.url,
+                title: bookmark.title,
+                source: SuggestionSource::Bookmark,
+                visits: 0,
+                last_visited: (bookmark.last_visited > 0).then_some(bookmark.last_visited as i64),
+            });
+        }
+
+        for tab in &self.tab_manager.borrow().tabs {
+            suggestions.push(Suggestion {
+                url: tab.url.clone(),
+                title: tab.name.clone(),
+                source: SuggestionSource::Tab,
+                visits: 0,
+                last_visited: None,
+            });
+        }
+
+        for item in self.history_manager.borrow().items() {
+            suggestions.push(Suggestion {
+                url: item.url.clone(),
+                title: item.title.clone(),
+                source: SuggestionSource::History,
+                visits: item.visit_count,
+                last_visited: Some(item.last_visited),
+            });
+        }
+
+        suggestions
+    }
+
+    /// Turns what has been typed in the URL bar into an url, see `resolve_url_input`
+    fn resolve_url_input(&self, input: &str) -> Option<String> {
+        resolve_url_input(
+            input,
+            &self.tab_manager.borrow().context.settings,
+            &self.bookmark_manager.borrow(),
+        )
+    }
+
+    /// Opens the search prompt for the current tab, filled in with any previous search
+    fn open_search(&mut self) {
+        let tab_idx = self.tab_manager.borrow().current;
+        let (query, options) = match &self.tab_manager.borrow().current().search {
+            Some(search) => (search.query.clone(), search.options),
+            None => (String::new(), SearchOptions::default()),
+        };
+
+        let inner = SearchWidget::new(tab_idx, &query, options);
+        let widget = Widget::new("input", false, Rc::new(RefCell::new(inner)));
+        self.widget_manager.create(widget);
+        self.command_queue.push(Command::ShowWidget {
+            id: "input".into(),
+            focus: true,
+        });
+    }
+
     pub(crate) fn process_commands(&mut self) {
         loop {
             match self.command_queue.pending() {
                 None => break,
                 Some(Command::Quit) => {
+                    self.save_session(true);
+                    self.history_manager.borrow_mut().save();
                     self.should_quit = true;
                     break;
                 }
@@ -259,11 +618,21 @@ impl App {
                         });
                     }
                     InputSubmitCommand::OpenTabWithUrl => {
-                        self.command_queue.push(Command::NewTabUrl {
-                            title: value.clone(),
-                            url: value.clone(),
-                        });
+                        if let Some(url) = self.resolve_url_input(&value) {
+                            self.command_queue.push(Command::NewTabUrl {
+                                title: url.clone(),
+                                url,
+                            });
+                        }
                     }
+                    InputSubmitCommand::NavigateTabToUrl { tab_idx } => {
+                        if let Some(url) = self.resolve_url_input(&value) {
+                            self.command_queue
+                                .push(Command::NavigateTab { idx: tab_idx, url });
+                        }
+                    }
+                    // The search widget searches while typing, and never submits
+                    InputSubmitCommand::Search { .. } => {}
                 },
                 Some(Command::RenameTab { tab_idx, name }) => {
                     self.tab_manager.borrow_mut().rename(tab_idx, &name);
@@ -295,6 +664,233 @@ impl App {
                         .borrow_mut()
                         .tab_info(Some(self.tab_manager.borrow().current().info()));
                 }
+                Some(Command::ContentLoaded { load_id, result }) => {
+                    let failed = result.is_err();
+                    let encoding = result
+                        .as_ref()
+                        .ok()
+                        .and_then(|content| content.encoding.clone());
+                    // Tabs in background workspaces keep loading, without updating the status bar
+                    if self.tab_manager.borrow().loading_tab(load_id).is_none() {
+                        let finished = self
+                            .workspace_manager
+                            .borrow_mut()
+                            .finish_load(load_id, result);
+                        if let Some((ws_idx, idx)) = finished.filter(|_| !failed) {
+                            let workspace_manager = self.workspace_manager.borrow();
+                            let tabs = workspace_manager.workspaces()[ws_idx].tabs();
+                            if let Some(tab) = tabs.and_then(|tabs| tabs.tabs.get(idx)) {
+                                self.record_visit(tab);
+                            }
+                        }
+                        continue;
+                    }
+
+                    let idx = self.tab_manager.borrow_mut().finish_load(load_id, result);
+                    if let Some(idx) = idx {
+                        if !failed {
+                            if let Some(tab) = self.tab_manager.borrow().tabs.get(idx) {
+                                self.record_visit(tab);
+                            }
+                        }
+                        self.status_bar.borrow_mut().status(
+                            match (failed, encoding) {
+                                (true, _) => format!("Failed to load tab {}", idx),
+                                (false, Some(encoding)) => {
+                                    format!("Loaded tab {} ({})", idx, encoding)
+                                }
+                                (false, None) => format!("Loaded tab {}", idx),
+                            }
+                            .as_str(),
+                        );
+                        self.status_bar
+                            .borrow_mut()
+                            .tab_info(Some(self.tab_manager.borrow().current().info()));
+                    }
+                }
+                Some(Command::Reload { idx, hard }) => {
+                    if self.tab_manager.borrow_mut().reload(idx, hard) {
+                        let status = if hard {
+                            format!("Hard reloading tab {}", idx)
+                        } else {
+                            format!("Reloading tab {}", idx)
+                        };
+                        self.status_bar.borrow_mut().status(status.as_str());
+                        self.status_bar
+                            .borrow_mut()
+                            .tab_info(Some(self.tab_manager.borrow().current().info()));
+                    }
+                }
+                Some(Command::NavigateTab { idx, url }) => {
+                    if self.tab_manager.borrow_mut().navigate(idx, &url) {
+                        self.status_bar
+                            .borrow_mut()
+                            .status(format!("Opening {}", url).as_str());
+                        self.status_bar
+                            .borrow_mut()
+                            .tab_info(Some(self.tab_manager.borrow().current().info()));
+                    }
+                }
+                Some(Command::SaveContent { idx }) => {
+                    // Saved files end up next to the bookmarks, in the working directory
+                    let result = match self.tab_manager.borrow().tabs.get(idx) {
+                        Some(tab) => tab.save_content(Path::new(".")),
+                        None => continue,
+                    };
+
+                    let status = match result {
+                        Ok(path) => format!("Saved to {}", path.display()),
+                        Err(e) => format!("Failed to save: {}", e),
+                    };
+                    self.status_bar.borrow_mut().status(status.as_str());
+                }
+                Some(Command::Search {
+                    idx,
+                    query,
+                    options,
+                }) => {
+                    if let Some(tab) = self.tab_manager.borrow_mut().tabs.get_mut(idx) {
+                        tab.search = Some(PageSearch::new(&query, options));
+                    }
+                }
+                Some(Command::ClearSearch { idx }) => {
+                    if let Some(tab) = self.tab_manager.borrow_mut().tabs.get_mut(idx) {
+                        tab.search = None;
+                    }
+                }
+                Some(Command::FollowLink {
+                    idx,
+                    number,
+                    new_tab,
+                }) => {
+                    let url = self
+                        .tab_manager
+                        .borrow()
+                        .tabs
+                        .get(idx)
+                        .and_then(|tab| tab.link_url(number));
+
+                    match url {
+                        Some(url) if new_tab => {
+                            self.command_queue.push(Command::NewTabUrl {
+                                title: url.clone(),
+                                url,
+                            });
+                        }
+                        Some(url) => {
+                            self.command_queue.push(Command::NavigateTab { idx, url });
+                        }
+                        None => {
+                            self.status_bar
+                                .borrow_mut()
+                                .status(format!("No link with number {}", number).as_str());
+                        }
+                    }
+                }
+                Some(Command::NavigateBack { idx }) => {
+                    if self.tab_manager.borrow_mut().back(idx) {
+                        self.status_bar.borrow_mut().status("Browsing back");
+                        self.status_bar
+                            .borrow_mut()
+                            .tab_info(Some(self.tab_manager.borrow().current().info()));
+                    } else {
+                        self.status_bar.borrow_mut().status("No previous page");
+                    }
+                }
+                Some(Command::NavigateForward { idx }) => {
+                    if self.tab_manager.borrow_mut().forward(idx) {
+                        self.status_bar.borrow_mut().status("Browsing forward");
+                        self.status_bar
+                            .borrow_mut()
+                            .tab_info(Some(self.tab_manager.borrow().current().info()));
+                    } else {
+                        self.status_bar.borrow_mut().status("No next page");
+                    }
+                }
+                Some(Command::ClearCache) => {
+                    let context = &self.tab_manager.borrow().context;
+                    context.cache.lock().expect("cache lock").clear();
+                    log::info!("HTTP cache cleared");
+                    self.status_bar.borrow_mut().status("Cache cleared");
+                }
+                Some(Command::CreateWorkspace { name }) => {
+                    let created = self
+                        .workspace_manager
+                        .borrow_mut()
+                        .create(&name, &self.tab_manager.borrow());
+                    match created {
+                        Some(idx) => {
+                            self.workspace_manager
+                                .borrow_mut()
+                                .switch(idx, &mut self.tab_manager.borrow_mut());
+                            self.status_bar
+                                .borrow_mut()
+                                .status(format!("Created workspace {}", name.trim()).as_str());
+                            self.update_workspace_status();
+                        }
+                        None => {
+                            self.status_bar.borrow_mut().status(
+                                format!("Can't create workspace '{}'", name.trim()).as_str(),
+                            );
+                        }
+                    }
+                }
+                Some(Command::SwitchWorkspace { idx }) => {
+                    if self
+                        .workspace_manager
+                        .borrow_mut()
+                        .switch(idx, &mut self.tab_manager.borrow_mut())
+                    {
+                        let name = self.workspace_manager.borrow().active_name().to_string();
+                        self.status_bar
+                            .borrow_mut()
+                            .status(format!("Switched to workspace {}", name).as_str());
+                        self.update_workspace_status();
+                    }
+                }
+                Some(Command::RenameWorkspace { idx, name }) => {
+                    if self.workspace_manager.borrow_mut().rename(idx, &name) {
+                        self.status_bar
+                            .borrow_mut()
+                            .status(format!("Renamed workspace to {}", name.trim()).as_str());
+                        self.update_workspace_status();
+                    } else {
+                        self.status_bar.borrow_mut().status(
+                            format!("Can't rename workspace to '{}'", name.trim()).as_str(),
+                        );
+                    }
+                }
+                Some(Command::DeleteWorkspace { idx }) => {
+                    let name = match self.workspace_manager.borrow().workspaces().get(idx) {
+                        Some(workspace) => workspace.name.clone(),
+                        None => continue,
+                    };
+
+                    if self
+                        .workspace_manager
+                        .borrow_mut()
+                        .delete(idx, &mut self.tab_manager.borrow_mut())
+                    {
+                        self.status_bar
+                            .borrow_mut()
+                            .status(format!("Deleted workspace {}", name).as_str());
+                        self.update_workspace_status();
+                    } else {
+                        self.status_bar
+                            .borrow_mut()
+                            .status("Can't delete last workspace");
+                    }
+                }
+                Some(Command::CancelLoad { idx }) => {
+                    if self.tab_manager.borrow_mut().cancel_load(idx) {
+                        self.status_bar
+                            .borrow_mut()
+                            .status(format!("Stopped loading tab {}", idx).as_str());
+                        self.status_bar
+                            .borrow_mut()
+                            .tab_info(Some(self.tab_manager.borrow().current().info()));
+                    }
+                }
             }
         }
     }
@@ -74,6 +74,18 @@ impl BookmarkManager {
     pub fn find_bookmark(&self, id: Uuid) -> Option<Bookmark> {
         find_bookmark_recursive(&self.root, id)
     }
+
+    /// Returns all bookmarks, from all folders
+    pub fn bookmarks(&self) -> Vec<Bookmark> {
+        let mut bookmarks = vec![];
+        collect_bookmarks_recursive(&self.root, &mut bookmarks);
+        bookmarks
+    }
+
+    /// Returns the first bookmark that has the given keyword, ignoring case
+    pub fn find_keyword(&self, keyword: &str) -> Option<Bookmark> {
+        find_keyword_recursive(&self.root, keyword)
+    }
 }
 
 fn read_bookmarks_config(file_path: &str) -> Result<Folder, serde_json::Error> {
@@ -97,6 +109,35 @@ fn find_folder_recursive(folder: &Folder, id: Uuid) -> Option<Folder> {
     None
 }
 
+fn collect_bookmarks_recursive(folder: &Folder, bookmarks: &mut Vec<Bookmark>) {
+    bookmarks.extend(folder.bookmarks.iter().cloned());
+
+    for subfolder in folder.subfolders.iter() {
+        collect_bookmarks_recursive(subfolder, bookmarks);
+    }
+}
+
+fn find_keyword_recursive(folder: &Folder, keyword: &str) -> Option<Bookmark> {
+    for bookmark in folder.bookmarks.iter() {
+        if bookmark
+            .keywords
+            .iter()
+            .any(|k| k.eq_ignore_ascii_case(keyword))
+        {
+            return Some(bookmark.clone());
+        }
+    }
+
+    for subfolder in folder.subfolders.iter() {
+        let found = find_keyword_recursive(subfolder, keyword);
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    None
+}
+
 #[allow(dead_code)]
 fn find_bookmark_recursive(folder: &Folder, id: Uuid) -> Option<Bookmark> {
     for bookmark in folder.bookmarks.iter() {
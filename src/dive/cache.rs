@@ -0,0 +1,562 @@
+use crate::dive::fetcher::HttpResponse;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use url::Url;
+use uuid::Uuid;
+
+/// Responses are considered fresh for this fraction of the time since they have last been
+/// modified, when the server doesn't say how long they can be cached
+const HEURISTIC_FRACTION: i64 = 10;
+/// Upper bound for the heuristic freshness of responses
+const MAX_HEURISTIC_LIFETIME: i64 = 24 * 60 * 60;
+
+/// A response that is kept in the cache. The body lives in a file of its own, next to the index.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    /// Size of the body in bytes
+    pub size: u64,
+    /// Unix time the response has been stored, or revalidated for the last time
+    pub stored: i64,
+    /// Number of seconds the response stays fresh after it has been stored
+    pub lifetime: i64,
+    /// Unix time the entry has last been used, so the least recently used entries are evicted
+    /// first
+    pub used: i64,
+    file: String,
+}
+
+impl CacheEntry {
+    pub fn is_fresh(&self, now: i64) -> bool {
+        now < self.stored + self.lifetime
+    }
+
+    /// Moment the response needs to be revalidated with the server
+    pub fn expires(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.stored + self.lifetime, 0)
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Outcome of looking up a url in the cache
+#[derive(Debug, PartialEq)]
+pub enum Lookup {
+    /// The cached response can be used without asking the server
+    Fresh(HttpResponse),
+    /// The cached response has to be revalidated by sending these conditional request headers
+    Stale(Vec<(String, String)>),
+    Miss,
+}
+
+/// Cache for HTTP responses, following the rules of RFC 9111 for a private cache. Responses are
+/// kept on disk, and the least recently used ones are evicted once the cache grows beyond its
+/// maximum size.
+pub struct HttpCache {
+    /// Directory with the index and the bodies. Nothing is cached when not set.
+    dir: Option<PathBuf>,
+    max_size: u64,
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl HttpCache {
+    /// Returns a cache that never stores anything
+    pub fn disabled() -> Self {
+        Self {
+            dir: None,
+            max_size: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Opens the cache in the given directory. A missing or invalid index results in an empty
+    /// cache.
+    pub fn new_from_dir(dir: PathBuf, max_size: u64) -> Self {
+        let entries = match fs::read_to_string(dir.join("index.json")) {
+            Ok(index) => serde_json::from_str(&index).unwrap_or_else(|e| {
+                log::warn!("Invalid cache index in {}: {}", dir.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        let mut cache = Self {
+            dir: Some(dir),
+            max_size,
+            entries,
+        };
+        // The maximum size might have been lowered since the last run
+        cache.evict();
+        cache
+    }
+
+    /// Returns the default location of the cache, inside the user's cache directory
+    pub fn default_dir() -> Option<PathBuf> {
+        Some(dirs::cache_dir()?.join("dive").join("http"))
+    }
+
+    /// Returns all entries, the most recently used first
+    pub fn entries(&self) -> Vec<&CacheEntry> {
+        let mut entries = self.entries.values().collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.used.cmp(&a.used).then_with(|| a.url.cmp(&b.url)));
+        entries
+    }
+
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Returns the combined size of all cached bodies
+    pub fn size(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+
+    pub fn lookup(&mut self, url: &Url) -> Lookup {
+        let now = Utc::now().timestamp();
+        let Some(entry) = self.entries.get(url.as_str()) else {
+            return Lookup::Miss;
+        };
+
+        if !entry.is_fresh(now) {
+            let mut headers = vec![];
+            if let Some(etag) = entry.header("ETag") {
+                headers.push(("If-None-Match".to_string(), etag.to_string()));
+            }
+            if let Some(last_modified) = entry.header("Last-Modified") {
+                headers.push(("If-Modified-Since".to_string(), last_modified.to_string()));
+            }
+            return Lookup::Stale(headers);
+        }
+
+        match self.response(url) {
+            Some(response) => Lookup::Fresh(response),
+            None => Lookup::Miss,
+        }
+    }
+
+    /// Stores the response to the given url, when it is allowed to be cached
+    pub fn store(&mut self, url: &Url, response: &HttpResponse) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+
+        let key = url.as_str().to_string();
+        let size = response.body.len() as u64;
+        if !is_cacheable(response) || size > self.max_size {
+            // Whatever we had is outdated by now
+            self.remove(&key);
+            return;
+        }
+
+        let now = Utc::now().timestamp();
+        // Cookies are handled by the cookie jar, and must not be set again from the cache
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| !name.eq_ignore_ascii_case("Set-Cookie"))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let file = match self.entries.get(&key) {
+            Some(entry) => entry.file.clone(),
+            None => format!("{}.body", Uuid::new_v4()),
+        };
+        let result =
+            fs::create_dir_all(dir).and_then(|_| fs::write(dir.join(&file), &response.body));
+        if let Err(e) = result {
+            log::error!("Unable to store {} in the cache: {}", url, e);
+            return;
+        }
+
+        self.entries.insert(
+            key,
+            CacheEntry {
+                url: url.to_string(),
+                status: response.status,
+                status_text: response.status_text.clone(),
+                lifetime: freshness_lifetime(&headers, now),
+                headers,
+                size,
+                stored: now,
+                used: now,
+                file,
+            },
+        );
+
+        self.evict();
+        self.save();
+    }
+
+    /// Updates the entry for the given url with the headers of a 304 Not Modified response, and
+    /// returns the full cached response. Returns None when the cached body is no longer
+    /// available.
+    pub fn revalidate(&mut self, url: &Url, headers: &[(String, String)]) -> Option<HttpResponse> {
+        let now = Utc::now().timestamp();
+        let entry = self.entries.get_mut(url.as_str())?;
+
+        for (name, value) in headers {
+            if name.eq_ignore_ascii_case("Set-Cookie")
+                || name.eq_ignore_ascii_case("Content-Length")
+            {
+                continue;
+            }
+            entry.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+            entry.headers.push((name.clone(), value.clone()));
+        }
+        entry.stored = now;
+        entry.lifetime = freshness_lifetime(&entry.headers, now);
+
+        let response = self.response(url);
+        self.save();
+        response
+    }
+
+    /// Removes all entries and their bodies
+    pub fn clear(&mut self) {
+        let keys = self.entries.keys().cloned().collect::<Vec<_>>();
+        for key in keys {
+            self.remove(&key);
+        }
+        self.save();
+    }
+
+    /// Reads the cached response for the given url, and marks it as used
+    fn response(&mut self, url: &Url) -> Option<HttpResponse> {
+        let dir = self.dir.as_ref()?;
+        let entry = self.entries.get_mut(url.as_str())?;
+
+        match fs::read(dir.join(&entry.file)) {
+            Ok(body) => {
+                entry.used = Utc::now().timestamp();
+                Some(HttpResponse {
+                    status: entry.status,
+                    status_text: entry.status_text.clone(),
+                    headers: entry.headers.clone(),
+                    body,
+                })
+            }
+            Err(e) => {
+                log::warn!("Dropping {} from the cache: {}", url, e);
+                self.entries.remove(url.as_str());
+                None
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        let (Some(dir), Some(entry)) = (&self.dir, self.entries.remove(key)) else {
+            return;
+        };
+        if let Err(e) = fs::remove_file(dir.join(&entry.file)) {
+            if e.kind() != io::ErrorKind::NotFound {
+                log::warn!("Unable to remove {} from the cache: {}", entry.url, e);
+            }
+        }
+    }
+
+    /// Removes the least recently used entries until the cache fits within its maximum size
+    fn evict(&mut self) {
+        let mut size = self.size();
+        if size <= self.max_size {
+            return;
+        }
+
+        let mut entries = self
+            .entries
+            .values()
+            .map(|entry| (entry.used, entry.url.clone(), entry.size))
+            .collect::<Vec<_>>();
+        entries.sort();
+
+        for (_, url, entry_size) in entries {
+            if size <= self.max_size {
+                break;
+            }
+            log::debug!("Evicting {} from the cache", url);
+            self.remove(&url);
+            size -= entry_size;
+        }
+    }
+
+    fn save(&self) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+
+        let result = fs::create_dir_all(dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string(&self.entries).map_err(|e| e.to_string()))
+            .and_then(|index| fs::write(dir.join("index.json"), index).map_err(|e| e.to_string()));
+
+        if let Err(e) = result {
+            log::error!("Unable to save the cache index to {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// Returns the directives of the Cache-Control header, with their names lowercased
+fn cache_control(headers: &[(String, String)]) -> Vec<(String, Option<String>)> {
+    headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("Cache-Control"))
+        .flat_map(|(_, value)| value.split(','))
+        .filter_map(|directive| {
+            let directive = directive.trim();
+            if directive.is_empty() {
+                return None;
+            }
+            Some(match directive.split_once('=') {
+                Some((name, value)) => (
+                    name.trim().to_lowercase(),
+                    Some(value.trim().trim_matches('"').to_string()),
+                ),
+                None => (directive.to_lowercase(), None),
+            })
+        })
+        .collect()
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn http_date(value: &str) -> Option<i64> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|date| date.timestamp())
+}
+
+/// Only complete responses that are not forbidden from being stored are cached. Responses that
+/// vary on anything but their encoding would need a cache entry per variant, so these are left
+/// out as well.
+fn is_cacheable(response: &HttpResponse) -> bool {
+    if response.status != 200 {
+        return false;
+    }
+    if cache_control(&response.headers)
+        .iter()
+        .any(|(name, _)| name == "no-store")
+    {
+        return false;
+    }
+    if let Some(vary) = response.header("Vary") {
+        if vary
+            .split(',')
+            .any(|field| !field.trim().eq_ignore_ascii_case("Accept-Encoding"))
+        {
+            return false;
+        }
+    }
+
+    // Without a lifetime or a validator, a cached response could never be used
+    freshness_lifetime(&response.headers, 0) > 0
+        || response.header("ETag").is_some()
+        || response.header("Last-Modified").is_some()
+}
+
+/// Returns the number of seconds a response with the given headers, received at the given
+/// time, stays fresh
+fn freshness_lifetime(headers: &[(String, String)], now: i64) -> i64 {
+    let directives = cache_control(headers);
+    if directives.iter().any(|(name, _)| name == "no-cache") {
+        return 0;
+    }
+
+    let age = header(headers, "Age")
+        .and_then(|age| age.trim().parse::<i64>().ok())
+        .unwrap_or(0);
+    let max_age = directives
+        .iter()
+        .find(|(name, _)| name == "max-age")
+        .and_then(|(_, value)| value.as_ref()?.parse::<i64>().ok());
+    if let Some(max_age) = max_age {
+        return (max_age - age).max(0);
+    }
+
+    let date = header(headers, "Date").and_then(http_date).unwrap_or(now);
+    if let Some(expires) = header(headers, "Expires") {
+        // Invalid dates, like "0", mean the response has already expired
+        return http_date(expires).map_or(0, |expires| (expires - date).max(0));
+    }
+
+    match header(headers, "Last-Modified").and_then(http_date) {
+        Some(last_modified) => {
+            ((date - last_modified) / HEURISTIC_FRACTION).clamp(0, MAX_HEURISTIC_LIFETIME)
+        }
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn response(response_headers: &[(&str, &str)], body: &str) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            status_text: "OK".into(),
+            headers: headers(response_headers),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn cache(max_size: u64) -> HttpCache {
+        let dir = std::env::temp_dir().join(format!("dive-cache-{}", Uuid::new_v4()));
+        HttpCache::new_from_dir(dir, max_size)
+    }
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn computes_the_freshness_lifetime() {
+        let date = "Wed, 21 Oct 2015 07:00:00 GMT";
+        assert_eq!(
+            freshness_lifetime(&headers(&[("Cache-Control", "public, max-age=60")]), 0),
+            60
+        );
+        assert_eq!(
+            freshness_lifetime(
+                &headers(&[("Cache-Control", "max-age=60"), ("Age", "20")]),
+                0
+            ),
+            40
+        );
+        assert_eq!(
+            freshness_lifetime(&headers(&[("Cache-Control", "no-cache, max-age=60")]), 0),
+            0
+        );
+        assert_eq!(
+            freshness_lifetime(
+                &headers(&[("Date", date), ("Expires", "Wed, 21 Oct 2015 08:00:00 GMT")]),
+                0
+            ),
+            3600
+        );
+        assert_eq!(
+            freshness_lifetime(&headers(&[("Date", date), ("Expires", "0")]), 0),
+            0
+        );
+        assert_eq!(
+            freshness_lifetime(
+                &headers(&[
+                    ("Date", date),
+                    ("Last-Modified", "Wed, 21 Oct 2015 06:00:00 GMT")
+                ]),
+                0
+            ),
+            360
+        );
+    }
+
+    #[test]
+    fn serves_fresh_responses() {
+        let mut cache = cache(1024);
+        let page = url("https://example.com/");
+        cache.store(
+            &page,
+            &response(&[("Cache-Control", "max-age=3600")], "Hello"),
+        );
+
+        match cache.lookup(&page) {
+            Lookup::Fresh(response) => assert_eq!(response.body, b"Hello"),
+            lookup => panic!("unexpected {:?}", lookup),
+        }
+        assert_eq!(
+            cache.lookup(&url("https://example.com/other")),
+            Lookup::Miss
+        );
+    }
+
+    #[test]
+    fn revalidates_stale_responses() {
+        let mut cache = cache(1024);
+        let page = url("https://example.com/");
+        cache.store(
+            &page,
+            &response(
+                &[
+                    ("Cache-Control", "no-cache"),
+                    ("ETag", "\"v1\""),
+                    ("Last-Modified", "Wed, 21 Oct 2015 07:00:00 GMT"),
+                ],
+                "Hello",
+            ),
+        );
+
+        assert_eq!(
+            cache.lookup(&page),
+            Lookup::Stale(headers(&[
+                ("If-None-Match", "\"v1\""),
+                ("If-Modified-Since", "Wed, 21 Oct 2015 07:00:00 GMT"),
+            ]))
+        );
+
+        let revalidated = cache
+            .revalidate(&page, &headers(&[("Cache-Control", "max-age=60")]))
+            .unwrap();
+        assert_eq!(revalidated.body, b"Hello");
+        assert!(matches!(cache.lookup(&page), Lookup::Fresh(_)));
+    }
+
+    #[test]
+    fn does_not_store_uncacheable_responses() {
+        let mut cache = cache(1024);
+        cache.store(
+            &url("https://example.com/a"),
+            &response(&[("Cache-Control", "no-store, max-age=60")], "A"),
+        );
+        cache.store(&url("https://example.com/b"), &response(&[], "B"));
+        cache.store(
+            &url("https://example.com/c"),
+            &response(&[("Cache-Control", "max-age=60"), ("Vary", "Cookie")], "C"),
+        );
+
+        assert!(cache.entries().is_empty());
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_entries() {
+        let mut cache = cache(10);
+        let fresh = [("Cache-Control", "max-age=3600")];
+        cache.store(&url("https://example.com/a"), &response(&fresh, "aaaa"));
+        cache.store(&url("https://example.com/b"), &response(&fresh, "bbbb"));
+        cache.entries.get_mut("https://example.com/a").unwrap().used -= 10;
+        cache.store(&url("https://example.com/c"), &response(&fresh, "cccc"));
+
+        let urls = cache
+            .entries()
+            .iter()
+            .map(|entry| entry.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(urls.len(), 2);
+        assert!(!urls.contains(&"https://example.com/a"));
+        assert_eq!(cache.size(), 8);
+
+        cache.clear();
+        assert!(cache.entries().is_empty());
+    }
+}
@@ -1,5 +1,9 @@
+use crate::dive::loader::{Content, LoadError};
+use crate::dive::search::SearchOptions;
 use crate::dive::widgets::input::InputSubmitCommand;
 use std::collections::VecDeque;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use uuid::Uuid;
 
 #[allow(dead_code)]
 #[derive(Debug, PartialEq)]
@@ -40,18 +44,85 @@ pub enum Command {
     CloseTab {
         idx: usize,
     },
+    /// Sent by a loader thread when the content for a tab has been fetched
+    ContentLoaded {
+        load_id: Uuid,
+        result: Result<Content, LoadError>,
+    },
+    CancelLoad {
+        idx: usize,
+    },
+    /// Reloads the given tab. A hard reload bypasses any caches.
+    Reload {
+        idx: usize,
+        hard: bool,
+    },
+    /// Saves the content of the given tab to disk
+    SaveContent {
+        idx: usize,
+    },
+    /// Navigates the given tab to a new url in place
+    NavigateTab {
+        idx: usize,
+        url: String,
+    },
+    /// Follows the link with the given number on the page of the given tab
+    FollowLink {
+        idx: usize,
+        number: usize,
+        new_tab: bool,
+    },
+    /// Searches for the given text in the page of the given tab
+    Search {
+        idx: usize,
+        query: String,
+        options: SearchOptions,
+    },
+    ClearSearch {
+        idx: usize,
+    },
+    NavigateBack {
+        idx: usize,
+    },
+    NavigateForward {
+        idx: usize,
+    },
+    /// Removes everything from the HTTP cache
+    ClearCache,
+    /// Adds a workspace with a blank tab, and switches to it
+    CreateWorkspace {
+        name: String,
+    },
+    SwitchWorkspace {
+        idx: usize,
+    },
+    RenameWorkspace {
+        idx: usize,
+        name: String,
+    },
+    /// Removes the given workspace and closes all its tabs
+    DeleteWorkspace {
+        idx: usize,
+    },
 }
 
 pub struct CommandQueue {
     commands: VecDeque<Command>,
+    /// Commands sent from other threads (like the page loaders) end up in this channel
+    sender: Sender<Command>,
+    receiver: Receiver<Command>,
 }
 
 impl CommandQueue {
     pub fn new() -> Self {
         // log::trace!("Creating new command queue");
 
+        let (sender, receiver) = channel();
+
         Self {
             commands: VecDeque::new(),
+            sender,
+            receiver,
         }
     }
 
@@ -60,8 +131,17 @@ impl CommandQueue {
         self.commands.push_back(command);
     }
 
+    /// Returns a sender that can be moved into other threads to push commands onto this queue
+    pub fn sender(&self) -> Sender<Command> {
+        self.sender.clone()
+    }
+
     #[allow(dead_code)]
     pub fn pending(&mut self) -> Option<Command> {
-        self.commands.pop_front()
+        if let Some(command) = self.commands.pop_front() {
+            return Some(command);
+        }
+
+        self.receiver.try_recv().ok()
     }
 }
@@ -0,0 +1,261 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Where a suggestion for the URL prompt comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SuggestionSource {
+    Bookmark,
+    Tab,
+    History,
+}
+
+impl fmt::Display for SuggestionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuggestionSource::Bookmark => write!(f, "bookmark"),
+            SuggestionSource::Tab => write!(f, "tab"),
+            SuggestionSource::History => write!(f, "history"),
+        }
+    }
+}
+
+/// A page that can be suggested in the URL prompt
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub url: String,
+    pub title: String,
+    pub source: SuggestionSource,
+    /// Number of times the page has been visited
+    pub visits: u32,
+    /// Unix time of the last visit, if known
+    pub last_visited: Option<i64>,
+}
+
+/// Returns the suggestions that match the typed text, best match first. Pages that are known
+/// from several sources are only suggested once, the source shown being the first one in the
+/// order bookmark, tab, history.
+///
+/// The typed text is matched fuzzily against both the title and the url: all its characters
+/// have to appear in order, and consecutive characters and matches at the start of words score
+/// higher. Pages that have been visited often or recently are ranked higher than others that
+/// match equally well.
+pub fn suggest(typed: &str, candidates: &[Suggestion], limit: usize) -> Vec<Suggestion> {
+    let now = Utc::now().timestamp();
+
+    let mut merged: HashMap<&str, Suggestion> = HashMap::new();
+    for candidate in candidates {
+        match merged.get_mut(candidate.url.as_str()) {
+            Some(existing) => {
+                existing.visits += candidate.visits;
+                existing.last_visited = existing.last_visited.max(candidate.last_visited);
+                if candidate.source < existing.source {
+                    existing.source = candidate.source;
+                }
+                if existing.title.is_empty() {
+                    existing.title = candidate.title.clone();
+                }
+            }
+            None => {
+                merged.insert(candidate.url.as_str(), candidate.clone());
+            }
+        }
+    }
+
+    let mut scored = merged
+        .into_values()
+        .filter_map(|suggestion| {
+            let title_score = fuzzy_score(typed, &suggestion.title);
+            let url_score = fuzzy_score(typed, strip_url(&suggestion.url));
+            let score = title_score.max(url_score)?;
+            Some((score * 10 + ranking_bonus(&suggestion, now), suggestion))
+        })
+        .collect::<Vec<_>>();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.url.len().cmp(&b.url.len()))
+            .then_with(|| a.url.cmp(&b.url))
+    });
+
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
+
+/// Leaves out the parts of an url that hardly anybody types, so "exa" is a prefix match for
+/// "https://www.example.com"
+fn strip_url(url: &str) -> &str {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    url.strip_prefix("www.").unwrap_or(url)
+}
+
+/// Scores how well the typed text matches the given text, or returns None when it doesn't
+/// match at all. Whitespace in the typed text is ignored.
+fn fuzzy_score(typed: &str, text: &str) -> Option<i64> {
+    let typed = typed.to_lowercase();
+    let text = text.to_lowercase();
+    let chars = text.chars().collect::<Vec<_>>();
+
+    let mut score = 0;
+    let mut pos = 0;
+    let mut previous: Option<usize> = None;
+    for c in typed.chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + chars[pos..].iter().position(|&t| t == c)?;
+
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        previous = Some(found);
+        pos = found + 1;
+    }
+
+    let typed = typed.trim();
+    if !typed.is_empty() && text.contains(typed) {
+        score += 10;
+        if text.starts_with(typed) {
+            score += 10;
+        }
+    }
+
+    Some(score)
+}
+
+/// Extra points for pages that have been visited often or recently, and for bookmarks and open
+/// tabs over plain history
+fn ranking_bonus(suggestion: &Suggestion, now: i64) -> i64 {
+    let frequency = (suggestion.visits as f64).ln_1p() * 15.0;
+    let recency = match suggestion.last_visited {
+        Some(last_visited) => {
+            let days = (now - last_visited).max(0) as f64 / (24.0 * 60.0 * 60.0);
+            40.0 / (1.0 + days)
+        }
+        None => 0.0,
+    };
+    let source = match suggestion.source {
+        SuggestionSource::Bookmark => 10.0,
+        SuggestionSource::Tab => 5.0,
+        SuggestionSource::History => 0.0,
+    };
+
+    (frequency + recency + source) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(url: &str, title: &str, source: SuggestionSource) -> Suggestion {
+        Suggestion {
+            url: url.into(),
+            title: title.into(),
+            source,
+            visits: 0,
+            last_visited: None,
+        }
+    }
+
+    fn urls(suggestions: Vec<Suggestion>) -> Vec<String> {
+        suggestions.into_iter().map(|s| s.url).collect()
+    }
+
+    #[test]
+    fn matches_titles_and_urls_fuzzily() {
+        let candidates = [
+            suggestion(
+                "https://www.rust-lang.org/",
+                "Rust",
+                SuggestionSource::Bookmark,
+            ),
+            suggestion(
+                "https://docs.rs/ratatui",
+                "ratatui docs",
+                SuggestionSource::History,
+            ),
+            suggestion("https://example.com/", "Example", SuggestionSource::Tab),
+        ];
+
+        assert_eq!(
+            urls(suggest("rust", &candidates, 10)),
+            vec!["https://www.rust-lang.org/"]
+        );
+        assert_eq!(
+            urls(suggest("rtt", &candidates, 10)),
+            vec!["https://docs.rs/ratatui"]
+        );
+        assert_eq!(suggest("", &candidates, 10).len(), 3);
+        assert_eq!(suggest("", &candidates, 2).len(), 2);
+    }
+
+    #[test]
+    fn prefers_better_matches() {
+        let candidates = [
+            suggestion("https://dontcallus.com/", "", SuggestionSource::History),
+            suggestion(
+                "https://developer.mozilla.org/docs",
+                "",
+                SuggestionSource::History,
+            ),
+            suggestion("https://docs.rs/", "", SuggestionSource::History),
+        ];
+
+        assert_eq!(
+            urls(suggest("docs", &candidates, 10)),
+            vec![
+                "https://docs.rs/",
+                "https://developer.mozilla.org/docs",
+                "https://dontcallus.com/"
+            ]
+        );
+    }
+
+    #[test]
+    fn ranks_frequent_and_recent_pages_higher() {
+        let now = Utc::now().timestamp();
+        let mut frequent = suggestion("https://a.example.com/", "", SuggestionSource::History);
+        frequent.visits = 20;
+        let mut recent = suggestion("https://b.example.com/", "", SuggestionSource::History);
+        recent.last_visited = Some(now);
+        let mut old = suggestion("https://c.example.com/", "", SuggestionSource::History);
+        old.visits = 1;
+        old.last_visited = Some(now - 365 * 24 * 60 * 60);
+
+        assert_eq!(
+            urls(suggest("example", &[old, recent, frequent], 10)),
+            vec![
+                "https://a.example.com/",
+                "https://b.example.com/",
+                "https://c.example.com/"
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_pages_from_several_sources() {
+        let mut history = suggestion("https://example.com/", "", SuggestionSource::History);
+        history.visits = 3;
+        let candidates = [
+            history.clone(),
+            suggestion(
+                "https://example.com/",
+                "Example",
+                SuggestionSource::Bookmark,
+            ),
+            history,
+        ];
+
+        let suggestions = suggest("exa", &candidates, 10);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].source, SuggestionSource::Bookmark);
+        assert_eq!(suggestions[0].title, "Example");
+        assert_eq!(suggestions[0].visits, 6);
+    }
+}
@@ -0,0 +1,243 @@
+use chrono::{DateTime, Local};
+use cookie_store::{CookieExpiration, CookieStore};
+use std::fs;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use url::Url;
+
+/// A cookie as listed in the cookie manager
+#[derive(Clone, Debug, PartialEq)]
+pub struct CookieInfo {
+    pub domain: String,
+    pub path: String,
+    pub name: String,
+    pub value: String,
+    /// Moment the cookie expires, or None for cookies that only last for this session
+    pub expires: Option<DateTime<Local>>,
+    pub secure: bool,
+    pub http_only: bool,
+}
+
+/// Cookies shared by all tabs. The expiry, domain and path rules of RFC 6265 are handled by the
+/// underlying cookie store. Persistent cookies are saved to disk whenever they change.
+pub struct CookieJar {
+    store: CookieStore,
+    /// File the persistent cookies are saved to. Cookies are kept in memory only when not set.
+    path: Option<PathBuf>,
+}
+
+impl CookieJar {
+    pub fn in_memory() -> Self {
+        Self {
+            store: CookieStore::default(),
+            path: None,
+        }
+    }
+
+    /// Loads the cookies from the given file. A missing or invalid file results in an empty
+    /// jar, that will be saved to the file once cookies are set.
+    pub fn new_from_file(path: PathBuf) -> Self {
+        let store = match fs::File::open(&path) {
+            Ok(file) => CookieStore::load_json(BufReader::new(file)).unwrap_or_else(|e| {
+                log::warn!("Unable to read cookies from {}: {}", path.display(), e);
+                CookieStore::default()
+            }),
+            Err(_) => CookieStore::default(),
+        };
+
+        Self {
+            store,
+            path: Some(path),
+        }
+    }
+
+    /// Returns the default location of the cookie file, inside the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join("dive").join("cookies.json"))
+    }
+
+    /// Returns the value for the Cookie header of a request to the given url, if there are any
+    /// cookies to send
+    pub fn request_header(&self, url: &Url) -> Option<String> {
+        let header = self
+            .store
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if header.is_empty() {
+            None
+        } else {
+            Some(header)
+        }
+    }
+
+    /// Stores the cookies from the Set-Cookie headers of a response to the given url
+    pub fn store_response(&mut self, url: &Url, headers: &[(String, String)]) {
+        let mut changed = false;
+        for (_, value) in headers
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("Set-Cookie"))
+        {
+            match self.store.parse(value, url) {
+                Ok(_) => changed = true,
+                Err(e) => log::debug!("Ignoring cookie from {}: {}", url, e),
+            }
+        }
+
+        if changed {
+            self.save();
+        }
+    }
+
+    /// Returns all cookies that have not expired yet, ordered by domain and name
+    pub fn cookies(&self) -> Vec<CookieInfo> {
+        let mut cookies = self
+            .store
+            .iter_unexpired()
+            .map(|cookie| CookieInfo {
+                domain: cookie
+                    .domain
+                    .as_cow()
+                    .map(|domain| domain.into_owned())
+                    .unwrap_or_default(),
+                path: String::from(&cookie.path),
+                name: cookie.name().to_string(),
+                value: cookie.value().to_string(),
+                expires: match cookie.expires {
+                    CookieExpiration::AtUtc(time) => {
+                        DateTime::from_timestamp(time.unix_timestamp(), 0)
+                            .map(|time| time.with_timezone(&Local))
+                    }
+                    CookieExpiration::SessionEnd => None,
+                },
+                secure: cookie.secure().unwrap_or(false),
+                http_only: cookie.http_only().unwrap_or(false),
+            })
+            .collect::<Vec<_>>();
+
+        cookies.sort_by(|a, b| a.domain.cmp(&b.domain).then_with(|| a.name.cmp(&b.name)));
+        cookies
+    }
+
+    pub fn remove(&mut self, cookie: &CookieInfo) {
+        self.store
+            .remove(&cookie.domain, &cookie.path, &cookie.name);
+        self.save();
+    }
+
+    /// Removes all cookies that have been set for the given domain
+    pub fn remove_domain(&mut self, domain: &str) {
+        for cookie in self.cookies() {
+            if cookie.domain == domain {
+                self.store
+                    .remove(&cookie.domain, &cookie.path, &cookie.name);
+            }
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::File::create(path))
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                self.store
+                    .save_json(&mut BufWriter::new(file))
+                    .map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            log::error!("Unable to save cookies to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    fn set_cookie(jar: &mut CookieJar, request_url: &str, cookie: &str) {
+        jar.store_response(&url(request_url), &[("Set-Cookie".into(), cookie.into())]);
+    }
+
+    #[test]
+    fn sends_cookies_back_to_matching_urls() {
+        let mut jar = CookieJar::in_memory();
+        set_cookie(&mut jar, "https://example.com/", "session=abc");
+        set_cookie(&mut jar, "https://example.com/", "lang=nl; Path=/docs");
+
+        assert_eq!(
+            jar.request_header(&url("https://example.com/")),
+            Some("session=abc".into())
+        );
+        let docs = jar
+            .request_header(&url("https://example.com/docs/intro"))
+            .unwrap();
+        assert!(docs.contains("session=abc") && docs.contains("lang=nl"));
+        assert_eq!(jar.request_header(&url("https://other.com/")), None);
+    }
+
+    #[test]
+    fn follows_domain_rules() {
+        let mut jar = CookieJar::in_memory();
+        set_cookie(
+            &mut jar,
+            "https://www.example.com/",
+            "shared=1; Domain=example.com",
+        );
+        set_cookie(&mut jar, "https://www.example.com/", "host=1");
+        set_cookie(
+            &mut jar,
+            "https://www.example.com/",
+            "evil=1; Domain=other.com",
+        );
+
+        assert_eq!(
+            jar.request_header(&url("https://api.example.com/")),
+            Some("shared=1".into())
+        );
+        assert_eq!(jar.request_header(&url("https://other.com/")), None);
+    }
+
+    #[test]
+    fn drops_expired_cookies() {
+        let mut jar = CookieJar::in_memory();
+        set_cookie(&mut jar, "https://example.com/", "old=1; Max-Age=0");
+        set_cookie(
+            &mut jar,
+            "https://example.com/",
+            "gone=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+        );
+
+        assert_eq!(jar.request_header(&url("https://example.com/")), None);
+        assert!(jar.cookies().is_empty());
+    }
+
+    #[test]
+    fn removes_cookies_per_domain() {
+        let mut jar = CookieJar::in_memory();
+        set_cookie(&mut jar, "https://example.com/", "a=1");
+        set_cookie(&mut jar, "https://example.com/", "b=2");
+        set_cookie(&mut jar, "https://other.com/", "c=3");
+
+        jar.remove_domain("example.com");
+        let cookies = jar.cookies();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].domain, "other.com");
+
+        jar.remove(&cookies[0]);
+        assert!(jar.cookies().is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::dive::fetcher::{Fetcher, HttpResponse};
+use crate::dive::loader::{LoadError, MAX_BODY_SIZE};
+use crate::dive::settings::Settings;
+use std::collections::HashSet;
+use std::io::Read;
+use std::time::Duration;
+use url::Url;
+
+/// Fetches over the actual network, using ureq. All requests go through a single agent, so
+/// connections to a server are kept open and reused between requests.
+pub struct UreqFetcher {
+    agent: ureq::Agent,
+}
+
+impl UreqFetcher {
+    /// Creates a fetcher with the user agent, timeouts and proxy from the given settings
+    pub fn new(settings: &Settings) -> Self {
+        // Redirects are followed by the loader, so it can keep track of them
+        let mut builder = ureq::AgentBuilder::new()
+            .redirects(0)
+            .user_agent(&settings.user_agent)
+            .timeout_connect(Duration::from_secs(settings.connect_timeout))
+            .timeout_read(Duration::from_secs(settings.read_timeout));
+
+        if let Some(proxy) = &settings.proxy {
+            match ureq::Proxy::new(proxy) {
+                Ok(proxy) => builder = builder.proxy(proxy),
+                Err(e) => log::error!("Ignoring invalid proxy '{}': {}", proxy, e),
+            }
+        }
+
+        Self {
+            agent: builder.build(),
+        }
+    }
+}
+
+impl Fetcher for UreqFetcher {
+    fn get(&self, url: &Url, headers: &[(&str, &str)]) -> Result<HttpResponse, LoadError> {
+        let mut request = self.agent.request_url("GET", url);
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+
+        match request.call() {
+            Ok(response) => read_response(response),
+            Err(ureq::Error::Status(_, response)) => read_response(response),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn read_response(response: ureq::Response) -> Result<HttpResponse, LoadError> {
+    let status = response.status();
+    let status_text = response.status_text().to_string();
+    // Headers like Set-Cookie can be repeated, so every value is kept under its own entry
+    let mut seen = HashSet::new();
+    let mut names = response.headers_names();
+    names.retain(|name| seen.insert(name.clone()));
+    let headers = names
+        .into_iter()
+        .flat_map(|name| {
+            response
+                .all(&name)
+                .into_iter()
+                .map(|value| (name.clone(), value.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    // Read one byte more than allowed, so a cut off body is not mistaken for a complete one
+    let mut body = vec![];
+    response
+        .into_reader()
+        .take(MAX_BODY_SIZE + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| LoadError::Decode(e.to_string()))?;
+    if body.len() as u64 > MAX_BODY_SIZE {
+        return Err(LoadError::TooLarge(MAX_BODY_SIZE));
+    }
+
+    Ok(HttpResponse {
+        status,
+        status_text,
+        headers,
+        body,
+    })
+}
+
+impl From<ureq::Error> for LoadError {
+    fn from(e: ureq::Error) -> Self {
+        match e {
+            ureq::Error::Status(code, response) => {
+                LoadError::HttpStatus(code, response.status_text().into())
+            }
+            ureq::Error::Transport(transport) => {
+                let msg = transport.to_string();
+
+                // Ureq does not have a separate error kind for TLS failures, but they all
+                // mention tls in their message.
+                if transport
+                    .message()
+                    .is_some_and(|m| m.to_lowercase().contains("tls"))
+                {
+                    return LoadError::Tls(msg);
+                }
+
+                match transport.kind() {
+                    ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => {
+                        LoadError::InvalidUrl(msg)
+                    }
+                    ureq::ErrorKind::BadStatus | ureq::ErrorKind::BadHeader => {
+                        LoadError::Decode(msg)
+                    }
+                    _ => LoadError::Connection(msg),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::dive::fetcher::{Fetcher, HttpResponse};
+use crate::dive::loader::LoadError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use url::Url;
+
+/// Url of a request with the headers that were sent along
+type Request = (String, Vec<(String, String)>);
+
+/// Serves canned responses from memory, for testing without a network. Urls without a canned
+/// response fail with a connection error. Clones share the log of requests, so a test can keep
+/// one around after handing the fetcher to a loader.
+#[derive(Default, Clone)]
+pub struct MockFetcher {
+    responses: HashMap<String, Result<HttpResponse, LoadError>>,
+    delays: HashMap<String, Duration>,
+    /// Urls and headers that have been requested, in order
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves the given body with a 200 status and the given content type
+    pub fn page(self, url: &str, content_type: &str, body: &str) -> Self {
+        self.response(
+            url,
+            HttpResponse {
+                status: 200,
+                status_text: "OK".into(),
+                headers: vec![("Content-Type".into(), content_type.into())],
+                body: body.as_bytes().to_vec(),
+            },
+        )
+    }
+
+    /// Serves an empty response with the given status
+    pub fn status(self, url: &str, status: u16, status_text: &str) -> Self {
+        self.response(
+            url,
+            HttpResponse {
+                status,
+                status_text: status_text.into(),
+                ..HttpResponse::default()
+            },
+        )
+    }
+
+    /// Redirects to the given location with the given status
+    pub fn redirect(self, url: &str, status: u16, location: &str) -> Self {
+        self.response(
+            url,
+            HttpResponse {
+                status,
+                status_text: "Redirect".into(),
+                headers: vec![("Location".into(), location.into())],
+                body: vec![],
+            },
+        )
+    }
+
+    pub fn response(mut self, url: &str, response: HttpResponse) -> Self {
+        self.responses.insert(url.into(), Ok(response));
+        self
+    }
+
+    /// Fails requests for the url with the given error
+    pub fn error(mut self, url: &str, error: LoadError) -> Self {
+        self.responses.insert(url.into(), Err(error));
+        self
+    }
+
+    /// Waits the given time before answering requests for the url
+    pub fn delay(mut self, url: &str, delay: Duration) -> Self {
+        self.delays.insert(url.into(), delay);
+        self
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        let requests = self.requests.lock().expect("requests lock");
+        requests.iter().map(|(url, _)| url.clone()).collect()
+    }
+
+    /// Returns the value of a header that was sent with the nth request
+    pub fn request_header(&self, index: usize, name: &str) -> Option<String> {
+        let requests = self.requests.lock().expect("requests lock");
+        let (_, headers) = requests.get(index)?;
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+impl Fetcher for MockFetcher {
+    fn get(&self, url: &Url, headers: &[(&str, &str)]) -> Result<HttpResponse, LoadError> {
+        let headers = headers
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        self.requests
+            .lock()
+            .expect("requests lock")
+            .push((url.to_string(), headers));
+
+        if let Some(delay) = self.delays.get(url.as_str()) {
+            thread::sleep(*delay);
+        }
+
+        match self.responses.get(url.as_str()) {
+            Some(response) => response.clone(),
+            None => Err(LoadError::Connection(format!("no response for {}", url))),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Network access of dive. Everything that goes over the network passes through a `Fetcher`,
+//! so the loading logic can be exercised without a network.
+
+use crate::dive::loader::LoadError;
+use url::Url;
+
+pub mod http;
+#[cfg(test)]
+pub mod mock;
+
+/// A response to a single HTTP request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    /// Headers in the order they have been received
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Returns the value of the first header with the given name, ignoring case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Performs HTTP requests for the loader
+pub trait Fetcher: Send + Sync {
+    /// Performs a single GET request with the given extra headers. Redirects are not followed,
+    /// and responses with an error status are returned like any other response. Only failing
+    /// to get a response at all is an error.
+    fn get(&self, url: &Url, headers: &[(&str, &str)]) -> Result<HttpResponse, LoadError>;
+}
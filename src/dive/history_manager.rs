@@ -0,0 +1,259 @@
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Number of pages we remember. The ones visited longest ago are forgotten first.
+const MAX_ITEMS: usize = 10_000;
+
+/// A page that has been visited, with the time of the last visit
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryItem {
+    pub id: Uuid,
+    pub url: String,
+    /// Title of the page at the last visit, empty for pages without a title
+    pub title: String,
+    /// Unix time of the last visit
+    pub last_visited: i64,
+    pub visit_count: u32,
+}
+
+impl HistoryItem {
+    pub fn last_visited(&self) -> DateTime<Local> {
+        Local
+            .timestamp_opt(self.last_visited, 0)
+            .single()
+            .unwrap_or_default()
+    }
+
+    /// Returns the day of the last visit, in local time
+    pub fn day(&self) -> NaiveDate {
+        self.last_visited().date_naive()
+    }
+}
+
+/// Global browsing history, shared by all tabs. Every page is listed once, the most recently
+/// visited first. Changes are saved to disk by calling save, so a page load never has to wait
+/// for the whole history to be written.
+pub struct HistoryManager {
+    items: Vec<HistoryItem>,
+    /// File the history is saved to. The history is only kept in memory when not set.
+    path: Option<PathBuf>,
+    /// Increased on every change, so views of the history know when to update
+    version: u64,
+    /// Version of the history that has last been written to disk
+    saved_version: u64,
+}
+
+impl HistoryManager {
+    pub fn empty() -> Self {
+        Self {
+            items: vec![],
+            path: None,
+            version: 0,
+            saved_version: 0,
+        }
+    }
+
+    /// Loads the history from the given file. A missing or invalid file results in an empty
+    /// history, that will be saved to the file on the first visit.
+    pub fn new_from_file(path: PathBuf) -> Self {
+        let items = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Unable to read history from {}: {}", path.display(), e);
+                vec![]
+            }),
+            Err(_) => vec![],
+        };
+
+        Self {
+            items,
+            path: Some(path),
+            version: 0,
+            saved_version: 0,
+        }
+    }
+
+    /// Returns the default location of the history file, inside the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join("dive").join("history.json"))
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns all visited pages, the most recently visited first
+    pub fn items(&self) -> &[HistoryItem] {
+        &self.items
+    }
+
+    /// Returns the pages whose url or title contains the given text, ignoring case
+    pub fn search(&self, query: &str) -> Vec<&HistoryItem> {
+        let query = query.to_lowercase();
+        self.items
+            .iter()
+            .filter(|item| {
+                item.url.to_lowercase().contains(&query)
+                    || item.title.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    /// Records a visit to the given url. An empty title keeps the title of earlier visits.
+    pub fn record_visit(&mut self, url: &str, title: &str) {
+        self.visit(url, title, Utc::now().timestamp());
+    }
+
+    fn visit(&mut self, url: &str, title: &str, time: i64) {
+        let item = match self.items.iter().position(|item| item.url == url) {
+            Some(idx) => {
+                let mut item = self.items.remove(idx);
+                item.visit_count += 1;
+                item.last_visited = time;
+                if !title.is_empty() {
+                    item.title = title.into();
+                }
+                item
+            }
+            None => HistoryItem {
+                id: Uuid::new_v4(),
+                url: url.into(),
+                title: title.into(),
+                last_visited: time,
+                visit_count: 1,
+            },
+        };
+
+        self.items.insert(0, item);
+        self.items.truncate(MAX_ITEMS);
+        self.version += 1;
+    }
+
+    /// Forgets about the pages with the given ids
+    pub fn remove(&mut self, ids: &[Uuid]) {
+        self.items.retain(|item| !ids.contains(&item.id));
+        self.version += 1;
+    }
+
+    /// Writes the history to its file, when it changed since the last save
+    pub fn save(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if self.version == self.saved_version {
+            return;
+        }
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string(&self.items).map_err(|e| e.to_string()))
+            .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+
+        match result {
+            Ok(()) => self.saved_version = self.version,
+            Err(e) => log::error!("Unable to save history to {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn urls(items: &[HistoryItem]) -> Vec<&str> {
+        items.iter().map(|item| item.url.as_str()).collect()
+    }
+
+    #[test]
+    fn lists_pages_once_most_recent_first() {
+        let mut history = HistoryManager::empty();
+        history.visit("https://a.example.com/", "A", 100);
+        history.visit("https://b.example.com/", "B", 200);
+        history.visit("https://a.example.com/", "", 300);
+
+        assert_eq!(
+            urls(history.items()),
+            vec!["https://a.example.com/", "https://b.example.com/"]
+        );
+        let item = &history.items()[0];
+        assert_eq!(item.visit_count, 2);
+        assert_eq!(item.last_visited, 300);
+        assert_eq!(item.title, "A");
+    }
+
+    #[test]
+    fn searches_urls_and_titles() {
+        let mut history = HistoryManager::empty();
+        history.visit(
+            "https://www.rust-lang.org/",
+            "Rust Programming Language",
+            100,
+        );
+        history.visit("https://docs.rs/", "Docs.rs", 200);
+
+        let found = history.search("programming");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].url, "https://www.rust-lang.org/");
+        assert_eq!(history.search("RS").len(), 1);
+        assert_eq!(history.search("").len(), 2);
+    }
+
+    #[test]
+    fn removes_pages() {
+        let mut history = HistoryManager::empty();
+        history.visit("https://a.example.com/", "", 100);
+        history.visit("https://b.example.com/", "", 200);
+        history.visit("https://c.example.com/", "", 300);
+
+        let ids = [history.items()[0].id, history.items()[2].id];
+        let version = history.version();
+        history.remove(&ids);
+        assert_eq!(urls(history.items()), vec!["https://b.example.com/"]);
+        assert!(history.version() > version);
+    }
+
+    #[test]
+    fn groups_by_day_of_the_last_visit() {
+        let now = Utc::now().timestamp();
+        let mut history = HistoryManager::empty();
+        history.visit("https://a.example.com/", "", now - 3 * DAY);
+        history.visit("https://b.example.com/", "", now);
+
+        let today = Local::now().date_naive();
+        assert_eq!(history.items()[0].day(), today);
+        assert!(history.items()[1].day() < today);
+    }
+
+    #[test]
+    fn saves_only_when_asked_and_changed() {
+        let path = std::env::temp_dir()
+            .join(format!("dive-history-{}", Uuid::new_v4()))
+            .join("history.json");
+        let mut history = HistoryManager::new_from_file(path.clone());
+
+        history.record_visit("https://example.com/", "Example");
+        assert!(!path.exists());
+
+        history.save();
+        let saved = HistoryManager::new_from_file(path.clone());
+        assert_eq!(urls(saved.items()), vec!["https://example.com/"]);
+
+        // Nothing changed, so the file is left alone
+        fs::remove_file(&path).unwrap();
+        history.save();
+        assert!(!path.exists());
+
+        history.remove(&[history.items()[0].id]);
+        history.save();
+        assert!(HistoryManager::new_from_file(path.clone())
+            .items()
+            .is_empty());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
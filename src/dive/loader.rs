@@ -0,0 +1,1215 @@
+use crate::dive::cache::{HttpCache, Lookup};
+use crate::dive::command_queue::Command;
+use crate::dive::cookies::CookieJar;
+use crate::dive::fetcher::{Fetcher, HttpResponse};
+use crate::dive::settings::Settings;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::{DecodePaddingMode, Engine};
+use chrono::{DateTime, Local, Utc};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use once_cell::sync::Lazy;
+use percent_encoding::percent_decode_str;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{cmp, fmt};
+use url::Url;
+use uuid::Uuid;
+
+/// The body of a loaded page. Only textual content is decoded into a string, anything else is
+/// kept as raw bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Body {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// A loaded page, together with the MIME type that decides how it is rendered
+#[derive(Debug, Clone, PartialEq)]
+pub struct Content {
+    pub mime_type: String,
+    pub body: Body,
+    /// Name of the encoding the body has been decoded from. Not set for binary content, or
+    /// content that was generated by dive itself.
+    pub encoding: Option<String>,
+    pub response: ResponseInfo,
+}
+
+/// Details about how content has been fetched, as shown in the page info dialog
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseInfo {
+    /// Url the content was eventually loaded from, after following any redirects
+    pub final_url: Option<String>,
+    /// HTTP status code and text. Only set for content fetched over HTTP.
+    pub status: Option<(u16, String)>,
+    pub headers: Vec<(String, String)>,
+    /// Redirects that were followed to get to the final url, in order
+    pub redirects: Vec<Redirect>,
+    /// Time it took to load the content
+    pub duration: Option<Duration>,
+    /// Whether the content came from the HTTP cache, either still fresh or revalidated
+    pub from_cache: bool,
+    /// Size of the body as it was received, before it has been decoded into text
+    pub size: Option<u64>,
+}
+
+/// A url that redirected us elsewhere while loading a page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirect {
+    pub status: u16,
+    pub url: String,
+}
+
+impl Content {
+    pub fn html(body: impl Into<String>) -> Self {
+        Self {
+            mime_type: "text/html".into(),
+            body: Body::Text(body.into()),
+            encoding: None,
+            response: ResponseInfo::default(),
+        }
+    }
+
+    pub fn plain(body: impl Into<String>) -> Self {
+        Self {
+            mime_type: "text/plain".into(),
+            body: Body::Text(body.into()),
+            encoding: None,
+            response: ResponseInfo::default(),
+        }
+    }
+
+    /// Creates content from raw bytes. Textual content is decoded from the given charset, or
+    /// the charset that is detected from the content itself.
+    pub fn from_bytes(mime_type: &str, charset: Option<&str>, bytes: Vec<u8>) -> Self {
+        let response = ResponseInfo {
+            size: Some(bytes.len() as u64),
+            ..ResponseInfo::default()
+        };
+
+        if !is_textual(mime_type) {
+            return Self {
+                mime_type: mime_type.into(),
+                body: Body::Binary(bytes),
+                encoding: None,
+                response,
+            };
+        }
+
+        let (text, encoding) = decode_text(&bytes, charset, mime_type);
+        Self {
+            mime_type: mime_type.into(),
+            body: Body::Text(text),
+            encoding: Some(encoding.name().into()),
+            response,
+        }
+    }
+
+    /// Returns the content as text, or None for binary content
+    pub fn as_text(&self) -> Option<&str> {
+        match &self.body {
+            Body::Text(text) => Some(text),
+            Body::Binary(_) => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.body {
+            Body::Text(text) => text.as_bytes(),
+            Body::Binary(bytes) => bytes,
+        }
+    }
+}
+
+/// Returns true when content of the given MIME type can be shown as text
+fn is_textual(mime_type: &str) -> bool {
+    mime_type.starts_with("text/")
+        || mime_type.ends_with("+json")
+        || mime_type.ends_with("+xml")
+        || matches!(
+            mime_type,
+            "application/json" | "application/xml" | "application/javascript"
+        )
+}
+
+/// Reasons why a page could not be loaded
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    /// The url could not be parsed, or uses a scheme we don't support
+    InvalidUrl(String),
+    /// No connection could be made to the host (dns failures, refused connections, timeouts etc)
+    Connection(String),
+    /// The secure connection could not be set up
+    Tls(String),
+    /// The server responded with a non-successful status code
+    HttpStatus(u16, String),
+    /// The response body could not be read or decoded
+    Decode(String),
+    /// The local file or directory does not exist
+    NotFound(String),
+    /// We are not allowed to read the local file or directory
+    PermissionDenied(String),
+    /// Any other failure while reading a local file or directory
+    Io(String),
+    /// The server kept redirecting us past the configured maximum
+    TooManyRedirects(u32),
+    /// The content is larger than we are willing to load into memory
+    TooLarge(u64),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::InvalidUrl(msg) => write!(f, "Invalid URL: {}", msg),
+            LoadError::Connection(msg) => write!(f, "Connection failed: {}", msg),
+            LoadError::Tls(msg) => write!(f, "Secure connection failed: {}", msg),
+            LoadError::HttpStatus(code, text) => write!(f, "HTTP error {} {}", code, text),
+            LoadError::Decode(msg) => write!(f, "Could not decode content: {}", msg),
+            LoadError::NotFound(path) => write!(f, "File not found: {}", path),
+            LoadError::PermissionDenied(path) => write!(f, "Permission denied: {}", path),
+            LoadError::Io(msg) => write!(f, "Could not read file: {}", msg),
+            LoadError::TooManyRedirects(max) => {
+                write!(f, "Too many redirects (followed {} already)", max)
+            }
+            LoadError::TooLarge(max) => {
+                write!(f, "Content is larger than {}", format_size(*max))
+            }
+        }
+    }
+}
+
+/// Largest body (of a response or a local file) we are willing to read into memory
+pub const MAX_BODY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Everything the loader threads share, besides the urls they load
+#[derive(Clone)]
+pub struct LoadContext {
+    pub settings: Settings,
+    /// Fetcher for anything that goes over the network
+    pub fetcher: Arc<dyn Fetcher>,
+    pub cookies: Arc<Mutex<CookieJar>>,
+    pub cache: Arc<Mutex<HttpCache>>,
+}
+
+/// Loads the given url on a separate thread. Once done, the result is sent back as a
+/// `Command::ContentLoaded` with the given load id, so the main loop can pick it up.
+/// A hard load will bypass any caches between us and the origin server.
+pub fn spawn_load(
+    sender: Sender<Command>,
+    load_id: Uuid,
+    url: &str,
+    hard: bool,
+    context: &LoadContext,
+) {
+    let url = url.to_string();
+    let context = context.clone();
+
+    thread::spawn(move || {
+        log::trace!("Loading {} ({})", url, load_id);
+
+        let start = Instant::now();
+        let result = load_content(&url, hard, &context).map(|mut content| {
+            content.response.duration = Some(start.elapsed());
+            content
+        });
+
+        // The receiving end is gone when the application is shutting down, so there is nobody
+        // left to care about this result.
+        let _ = sender.send(Command::ContentLoaded { load_id, result });
+    });
+}
+
+fn load_content(url: &str, hard: bool, context: &LoadContext) -> Result<Content, LoadError> {
+    let parts = match Url::parse(url) {
+        Ok(parts) => parts,
+        Err(e) => return Err(LoadError::InvalidUrl(e.to_string())),
+    };
+
+    if parts.scheme() == "file" {
+        return load_file(&parts);
+    }
+    if parts.scheme() == "data" {
+        return load_data(&parts);
+    }
+    if parts.scheme() == "gosub" {
+        return process_gosub_protocol(parts, context);
+    }
+    if parts.scheme() == "view-source" {
+        // The parsed url, as the raw one may still have leading whitespace
+        let inner = parts
+            .as_str()
+            .strip_prefix("view-source:")
+            .unwrap_or_default();
+        return load_source(inner, hard, context);
+    }
+
+    if parts.scheme() == "" || parts.scheme() == "https" {
+        return fetch(parts, hard, context);
+    }
+    if parts.scheme() == "" || parts.scheme() == "http" {
+        log::warn!("Opening insecure connection to {}", url);
+        return fetch(parts, hard, context);
+    }
+
+    Err(LoadError::InvalidUrl(format!(
+        "unsupported scheme '{}'",
+        parts.scheme()
+    )))
+}
+
+/// Fetches the given url over HTTP(S). Redirects are followed by hand, so we can record where
+/// we have been and stop after the configured number of redirects.
+fn fetch(url: Url, hard: bool, context: &LoadContext) -> Result<Content, LoadError> {
+    let settings = &context.settings;
+
+    let mut url = url;
+    let mut redirects = vec![];
+    let (response, from_cache) = loop {
+        let cookie = context
+            .cookies
+            .lock()
+            .expect("cookie jar lock")
+            .request_header(&url);
+
+        let mut headers = vec![];
+        if !settings.accept_language.is_empty() {
+            headers.push(("Accept-Language", settings.accept_language.as_str()));
+        }
+        headers.extend(settings.headers_for(url.host_str().unwrap_or_default()));
+        if hard {
+            headers.push(("Cache-Control", "no-cache"));
+            headers.push(("Pragma", "no-cache"));
+        }
+        if let Some(cookie) = &cookie {
+            headers.push(("Cookie", cookie.as_str()));
+        }
+
+        let (response, from_cache) = cached_get(&url, hard, &headers, context)?;
+
+        // Redirects often come with cookies, think of login pages
+        context
+            .cookies
+            .lock()
+            .expect("cookie jar lock")
+            .store_response(&url, &response.headers);
+
+        let status = response.status;
+        let location = response.header("Location");
+        let (true, Some(location)) = ((300..400).contains(&status), location) else {
+            break (response, from_cache);
+        };
+
+        if redirects.len() >= settings.max_redirects as usize {
+            return Err(LoadError::TooManyRedirects(settings.max_redirects));
+        }
+
+        let next = url.join(location).map_err(|e| {
+            LoadError::InvalidUrl(format!("invalid redirect '{}': {}", location, e))
+        })?;
+        if next.scheme() != "http" && next.scheme() != "https" {
+            return Err(LoadError::InvalidUrl(format!(
+                "redirect to unsupported scheme '{}'",
+                next.scheme()
+            )));
+        }
+
+        log::debug!("{} redirected ({}) to {}", url, status, next);
+        redirects.push(Redirect {
+            status,
+            url: url.to_string(),
+        });
+        url = next;
+    };
+
+    if response.status >= 400 {
+        return Err(LoadError::HttpStatus(response.status, response.status_text));
+    }
+
+    let info = ResponseInfo {
+        final_url: Some(url.to_string()),
+        status: Some((response.status, response.status_text.clone())),
+        headers: response.headers.clone(),
+        redirects,
+        duration: None,
+        from_cache,
+        size: Some(response.body.len() as u64),
+    };
+
+    // Servers that don't tell us what they send get sniffed like local files
+    let mut content = match response.header("Content-Type") {
+        Some(content_type) => {
+            let (mime_type, charset) = parse_content_type(content_type);
+            Content::from_bytes(&mime_type, charset.as_deref(), response.body)
+        }
+        None => Content::from_bytes(sniff_mime(&response.body), None, response.body),
+    };
+    content.response = info;
+
+    Ok(content)
+}
+
+/// Gets the given url through the HTTP cache. Fresh responses are served from the cache, stale
+/// ones are revalidated with a conditional request. A hard load skips the lookup, but still
+/// stores what it gets. Returns the response, and whether its body came from the cache.
+fn cached_get(
+    url: &Url,
+    hard: bool,
+    headers: &[(&str, &str)],
+    context: &LoadContext,
+) -> Result<(HttpResponse, bool), LoadError> {
+    let lookup = if hard {
+        Lookup::Miss
+    } else {
+        context.cache.lock().expect("cache lock").lookup(url)
+    };
+
+    let validators = match lookup {
+        Lookup::Fresh(response) => {
+            log::debug!("Serving {} from the cache", url);
+            return Ok((response, true));
+        }
+        Lookup::Stale(validators) => validators,
+        Lookup::Miss => vec![],
+    };
+
+    let mut conditional = headers.to_vec();
+    conditional.extend(
+        validators
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    );
+    let mut response = context.fetcher.get(url, &conditional)?;
+
+    if response.status == 304 && !validators.is_empty() {
+        let cached = context
+            .cache
+            .lock()
+            .expect("cache lock")
+            .revalidate(url, &response.headers);
+        if let Some(cached) = cached {
+            log::debug!("Revalidated {} in the cache", url);
+            return Ok((cached, true));
+        }
+
+        // The cached body has gone missing, so ask again without any conditions
+        response = context.fetcher.get(url, headers)?;
+    }
+
+    context
+        .cache
+        .lock()
+        .expect("cache lock")
+        .store(url, &response);
+    Ok((response, false))
+}
+
+/// Splits a Content-Type header like "text/html; charset=utf-8" into its lowercased MIME type
+/// and the charset, if any
+fn parse_content_type(content_type: &str) -> (String, Option<String>) {
+    let mut params = content_type.split(';').map(str::trim);
+
+    let mime_type = params.next().unwrap_or_default().to_lowercase();
+    let charset = params.find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"').to_string())
+    });
+
+    if mime_type.contains('/') {
+        (mime_type, charset)
+    } else {
+        ("application/octet-stream".into(), charset)
+    }
+}
+
+/// Loads the given url, and returns its source as text to be shown with syntax highlighting
+fn load_source(url: &str, hard: bool, context: &LoadContext) -> Result<Content, LoadError> {
+    let content = load_content(url, hard, context)?;
+
+    // There is no source to show for binary content
+    let Some(text) = content.as_text() else {
+        return Ok(content);
+    };
+
+    let mime_type = match content.mime_type.as_str() {
+        "text/html" | "application/xhtml+xml" => "text/x-view-source-html",
+        "text/css" => "text/x-view-source-css",
+        "text/javascript" | "application/javascript" => "text/x-view-source-javascript",
+        _ => "text/x-view-source",
+    };
+
+    Ok(Content {
+        mime_type: mime_type.into(),
+        body: Body::Text(text.into()),
+        encoding: content.encoding.clone(),
+        response: ResponseInfo {
+            final_url: content
+                .response
+                .final_url
+                .as_ref()
+                .map(|final_url| format!("view-source:{}", final_url)),
+            ..content.response.clone()
+        },
+    })
+}
+
+/// Reads a local file, or generates a listing when the url points to a directory
+fn load_file(url: &Url) -> Result<Content, LoadError> {
+    let path = url
+        .to_file_path()
+        .map_err(|_| LoadError::InvalidUrl(format!("'{}' is not a local path", url)))?;
+
+    let metadata = fs::metadata(&path).map_err(|e| file_error(&path, e))?;
+    if metadata.is_dir() {
+        return directory_listing(&path);
+    }
+
+    // Read one byte more than allowed, to find out whether the file is too large
+    let mut bytes = vec![];
+    fs::File::open(&path)
+        .and_then(|file| file.take(MAX_BODY_SIZE + 1).read_to_end(&mut bytes))
+        .map_err(|e| file_error(&path, e))?;
+    if bytes.len() as u64 > MAX_BODY_SIZE {
+        return Err(LoadError::TooLarge(MAX_BODY_SIZE));
+    }
+    let mime_type = mime_from_extension(&path).unwrap_or_else(|| sniff_mime(&bytes));
+
+    Ok(Content::from_bytes(mime_type, None, bytes))
+}
+
+fn file_error(path: &Path, e: io::Error) -> LoadError {
+    let path = path.display().to_string();
+    match e.kind() {
+        io::ErrorKind::NotFound => LoadError::NotFound(path),
+        io::ErrorKind::PermissionDenied => LoadError::PermissionDenied(path),
+        _ => LoadError::Io(format!("{}: {}", path, e)),
+    }
+}
+
+/// Returns the MIME type for the extension of the given path, if we know it
+fn mime_from_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+
+    let mime_type = match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "xhtml" => "application/xhtml+xml",
+        "txt" | "text" | "log" | "csv" | "ini" | "toml" | "yaml" | "yml" | "rs" | "c" | "h"
+        | "py" | "sh" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "json" => "application/json",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "xml" => "text/xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        _ => return None,
+    };
+
+    Some(mime_type)
+}
+
+/// Guesses the MIME type of content without a known extension by looking at its first bytes
+fn sniff_mime(bytes: &[u8]) -> &'static str {
+    let head = &bytes[..cmp::min(bytes.len(), 1024)];
+
+    // Text files hardly ever contain NUL bytes, while binary files nearly always do
+    if head.contains(&0) {
+        return "application/octet-stream";
+    }
+
+    let text = String::from_utf8_lossy(head).trim_start().to_lowercase();
+    let text = text.trim_start_matches('\u{feff}');
+    if text.starts_with("<!doctype html") || text.starts_with("<html") {
+        return "text/html";
+    }
+    if text.starts_with("<?xml") {
+        return "text/xml";
+    }
+
+    "text/plain"
+}
+
+/// Decodes a data: url, like "data:text/html;charset=utf-8,<h1>Hello</h1>" or
+/// "data:;base64,SGVsbG8=". Without a media type, the content is plain text.
+fn load_data(url: &Url) -> Result<Content, LoadError> {
+    // The fragment is not part of the data
+    let data = url.as_str()["data:".len()..]
+        .split('#')
+        .next()
+        .unwrap_or("");
+
+    let Some((header, payload)) = data.split_once(',') else {
+        return Err(LoadError::InvalidUrl(
+            "data url without a ',' separator".into(),
+        ));
+    };
+
+    let mut params = header.split(';').map(str::trim).collect::<Vec<_>>();
+    let base64 = params
+        .last()
+        .is_some_and(|param| param.eq_ignore_ascii_case("base64"));
+    if base64 {
+        params.pop();
+    }
+
+    let mime_type = match params.first() {
+        Some(mime_type) if mime_type.contains('/') => mime_type.to_lowercase(),
+        _ => "text/plain".into(),
+    };
+    let charset = params.iter().skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    });
+
+    let mut bytes = percent_decode_str(payload).collect::<Vec<_>>();
+    if base64 {
+        // Padding is often left out, and long payloads tend to be wrapped over several lines
+        bytes.retain(|b| !b.is_ascii_whitespace());
+        let engine = GeneralPurpose::new(
+            &base64::alphabet::STANDARD,
+            GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        );
+        bytes = engine
+            .decode(&bytes)
+            .map_err(|e| LoadError::Decode(format!("invalid base64 data: {}", e)))?;
+    }
+
+    Ok(Content::from_bytes(&mime_type, charset, bytes))
+}
+
+/// Decodes text into a string. The encoding is taken from, in order of precedence: a byte order
+/// mark, the given charset, a <meta> charset declaration in HTML documents. Without any of these,
+/// text that is valid UTF-8 is taken as such, and anything else as Windows-1252 like browsers do
+/// for legacy pages. Malformed sequences are replaced instead of failing the whole page.
+fn decode_text(
+    bytes: &[u8],
+    charset: Option<&str>,
+    mime_type: &str,
+) -> (String, &'static Encoding) {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return (text.into_owned(), encoding);
+    }
+
+    let declared = charset
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .or_else(|| {
+            if mime_type == "text/html" {
+                meta_charset(bytes)
+            } else {
+                None
+            }
+        });
+
+    let encoding = match declared {
+        Some(encoding) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => WINDOWS_1252,
+    };
+
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    (text.into_owned(), encoding)
+}
+
+/// Finds the charset declared by a <meta charset> or <meta http-equiv="Content-Type"> element
+/// in the first 1024 bytes of a HTML document
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    static META_CHARSET: Lazy<regex::bytes::Regex> = Lazy::new(|| {
+        regex::bytes::Regex::new(r#"(?i)<meta[^>]*?charset\s*=\s*["']?\s*([a-z0-9_:.\-]+)"#)
+            .expect("valid regex")
+    });
+
+    let head = &bytes[..cmp::min(bytes.len(), 1024)];
+    let label = META_CHARSET.captures(head)?.get(1)?.as_bytes();
+    let encoding = Encoding::for_label(label)?;
+
+    // A document that can be read as ASCII cannot be UTF-16, so the declaration must be wrong
+    if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE {
+        return Some(UTF_8);
+    }
+    Some(encoding)
+}
+
+/// Generates a HTML page listing the entries of a local directory. Directories are listed
+/// first, and every entry links to its own file:// url.
+fn directory_listing(path: &Path) -> Result<Content, LoadError> {
+    let mut entries = vec![];
+    for entry in fs::read_dir(path).map_err(|e| file_error(path, e))? {
+        // Entries can disappear while we are listing, these are just left out
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        entries.push((entry.file_name().to_string_lossy().into_owned(), metadata));
+    }
+    entries.sort_by(|(a_name, a), (b_name, b)| {
+        b.is_dir()
+            .cmp(&a.is_dir())
+            .then_with(|| a_name.to_lowercase().cmp(&b_name.to_lowercase()))
+    });
+
+    let title = escape_html(&path.display().to_string());
+    let mut html = format!("<h1>Index of {}</h1>\n\n<table>\n", title);
+    html.push_str("<tr><th>Name</th><th>Size</th><th>Modified</th></tr>\n");
+
+    if let Some(parent) = path.parent().and_then(|p| Url::from_directory_path(p).ok()) {
+        html.push_str(&format!(
+            "<tr><td><a href=\"{}\">../</a></td><td></td><td></td></tr>\n",
+            escape_html(parent.as_str())
+        ));
+    }
+
+    for (name, metadata) in entries {
+        let entry_path = path.join(&name);
+        let (url, name, size) = if metadata.is_dir() {
+            (
+                Url::from_directory_path(&entry_path),
+                format!("{}/", name),
+                String::new(),
+            )
+        } else {
+            (
+                Url::from_file_path(&entry_path),
+                name,
+                format_size(metadata.len()),
+            )
+        };
+        let Ok(url) = url else {
+            continue;
+        };
+
+        let modified = metadata
+            .modified()
+            .map(|time| {
+                DateTime::<Local>::from(time)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        html.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(url.as_str()),
+            escape_html(&name),
+            size,
+            modified
+        ));
+    }
+    html.push_str("</table>\n");
+
+    Ok(Content::html(html))
+}
+
+/// Formats a file size in a short human readable form, like "12.3 KB"
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+
+    let mut size = size as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn process_gosub_protocol(url: Url, context: &LoadContext) -> Result<Content, LoadError> {
+    let body = match url.host_str() {
+        Some("blank") => "This page is left intentionally blank".into(),
+        Some("help") => gosub_help(),
+        Some("cache") => gosub_cache(&context.cache.lock().expect("cache lock")),
+        Some("credits") => "Here be credits for the gosub engine".into(),
+        Some("settings") => "Here you can tinker with all kinds of dive and gosub settings".into(),
+        _ => "Unknown gosub protocol".into(),
+    };
+
+    Ok(Content::html(body))
+}
+
+fn gosub_help() -> String {
+    r#"<h1>gosub://help</h1>
+
+    <p>This is the help page for the gosub engine</p>
+
+    <p>The following special gosub pages are supported:</p>
+
+    <table>
+      <tr><td><a target="_blank"href="gosub://blank">gosub://blank</td><td>Opens a blank page</td></tr>
+      <tr><td><a target="_blank"href="gosub://help">gosub://help</td><td>Displays this help page</td></tr>
+      <tr><td><a target="_blank" href="gosub://credits">gosub://credits</td><td>Displays credits of the Dive Browser and the Gosub Engine</td></tr>
+      <tr><td><a target="_blank" href="gosub://settings">gosub://settings</td><td>Displays the settings page</td></tr>
+      <tr><td><a target="_blank" href="gosub://cache">gosub://cache</td><td>Lists the pages in the HTTP cache</td></tr>
+    </table>
+    "#
+    .into()
+}
+
+/// Generates the gosub://cache page, listing what is in the HTTP cache
+fn gosub_cache(cache: &HttpCache) -> String {
+    let entries = cache.entries();
+
+    let mut html = format!(
+        "<h1>gosub://cache</h1>\n\n<p>{} entries, using {} of {}. Press ALT-C to clear the cache.</p>\n\n",
+        entries.len(),
+        format_size(cache.size()),
+        format_size(cache.max_size())
+    );
+    if entries.is_empty() {
+        html.push_str("<p>The cache is empty.</p>\n");
+        return html;
+    }
+
+    let now = Utc::now().timestamp();
+    html.push_str("<table>\n");
+    html.push_str("<tr><th>URL</th><th>Size</th><th>Expires</th><th>Last used</th></tr>\n");
+    for entry in entries {
+        let expires = match entry.expires() {
+            _ if !entry.is_fresh(now) => "stale".to_string(),
+            Some(expires) => DateTime::<Local>::from(expires)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            None => "-".to_string(),
+        };
+        let used = DateTime::from_timestamp(entry.used, 0)
+            .map(|used| {
+                DateTime::<Local>::from(used)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        html.push_str(&format!(
+            "<tr><td><a href=\"{url}\">{url}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            format_size(entry.size),
+            expires,
+            used,
+            url = escape_html(&entry.url),
+        ));
+    }
+    html.push_str("</table>\n");
+
+    html
+}
+
+/// Generates the internal gosub://error page that is shown when the given url failed to load
+pub fn error_page(url: &str, error: &LoadError) -> Content {
+    Content::html(format!(
+        r#"<h1>gosub://error</h1>
+
+    <p>Dive was unable to load the page at <a href="{url}">{url}</a></p>
+
+    <p><b>Reason:</b> {reason}</p>
+
+    <p>Press CTRL-R to try loading the page again.</p>
+    "#,
+        url = escape_html(url),
+        reason = escape_html(&error.to_string()),
+    ))
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dive::fetcher::mock::MockFetcher;
+
+    fn context(fetcher: &MockFetcher, settings: Settings) -> LoadContext {
+        LoadContext {
+            settings,
+            fetcher: Arc::new(fetcher.clone()),
+            cookies: Arc::new(Mutex::new(CookieJar::in_memory())),
+            cache: Arc::new(Mutex::new(HttpCache::disabled())),
+        }
+    }
+
+    fn load(url: &str, fetcher: &MockFetcher) -> Result<Content, LoadError> {
+        load_content(url, false, &context(fetcher, Settings::default()))
+    }
+
+    #[test]
+    fn routes_gosub_urls_without_network() {
+        let fetcher = MockFetcher::new();
+
+        let content = load("gosub://help", &fetcher).unwrap();
+        assert_eq!(content.mime_type, "text/html");
+        assert!(content.as_text().unwrap().contains("gosub://help"));
+        assert!(fetcher.requests().is_empty());
+    }
+
+    #[test]
+    fn routes_data_urls_without_network() {
+        let fetcher = MockFetcher::new();
+
+        let content = load("data:text/plain;base64,SGVsbG8=", &fetcher).unwrap();
+        assert_eq!(content.mime_type, "text/plain");
+        assert_eq!(content.as_text(), Some("Hello"));
+        assert!(fetcher.requests().is_empty());
+    }
+
+    fn load_data_url(url: &str) -> Result<Content, LoadError> {
+        load_data(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn decodes_percent_encoded_data_urls() {
+        let content = load_data_url("data:text/html,%3Cp%3EHello%20world%3C/p%3E").unwrap();
+        assert_eq!(content.mime_type, "text/html");
+        assert_eq!(content.as_text(), Some("<p>Hello world</p>"));
+
+        // Without a media type, the data is plain text
+        let content = load_data_url("data:,Hello").unwrap();
+        assert_eq!(content.mime_type, "text/plain");
+        assert_eq!(content.as_text(), Some("Hello"));
+        let content = load_data_url("data:;charset=utf-8,Hello").unwrap();
+        assert_eq!(content.mime_type, "text/plain");
+
+        // The fragment is not part of the data
+        let content = load_data_url("data:,Hello#world").unwrap();
+        assert_eq!(content.as_text(), Some("Hello"));
+    }
+
+    #[test]
+    fn decodes_data_urls_with_a_charset() {
+        let content = load_data_url("data:text/plain;charset=iso-8859-1,caf%E9").unwrap();
+        assert_eq!(content.as_text(), Some("café"));
+        assert_eq!(content.encoding.as_deref(), Some("windows-1252"));
+
+        let content = load_data_url("data:text/plain;charset=\"UTF-8\";base64,Y2Fmw6k=").unwrap();
+        assert_eq!(content.as_text(), Some("café"));
+        assert_eq!(content.encoding.as_deref(), Some("UTF-8"));
+    }
+
+    #[test]
+    fn decodes_base64_without_padding_and_with_whitespace() {
+        let content = load_data_url("data:text/plain;base64,SGVsbG8").unwrap();
+        assert_eq!(content.as_text(), Some("Hello"));
+
+        let content = load_data_url("data:text/plain;BASE64,SGVs%20bG8g%0Ad29y%0D%0AbGQ=").unwrap();
+        assert_eq!(content.as_text(), Some("Hello world"));
+    }
+
+    #[test]
+    fn rejects_invalid_data_urls() {
+        let result = load_data_url("data:text/plain;base64,SGVsbG8*");
+        assert!(matches!(result, Err(LoadError::Decode(_))));
+
+        let result = load_data_url("data:text/plain;base64");
+        assert!(matches!(result, Err(LoadError::InvalidUrl(_))));
+    }
+
+    fn decode(bytes: &[u8], charset: Option<&str>, mime_type: &str) -> (String, &'static str) {
+        let (text, encoding) = decode_text(bytes, charset, mime_type);
+        (text, encoding.name())
+    }
+
+    #[test]
+    fn detects_the_charset_from_a_bom_first() {
+        let bytes = b"\xEF\xBB\xBF<meta charset=\"windows-1252\">caf\xC3\xA9";
+        let (text, encoding) = decode(bytes, Some("iso-8859-1"), "text/html");
+        assert_eq!(text, "<meta charset=\"windows-1252\">café");
+        assert_eq!(encoding, "UTF-8");
+
+        let (text, encoding) = decode(b"\xFF\xFEh\0i\0", None, "text/plain");
+        assert_eq!(text, "hi");
+        assert_eq!(encoding, "UTF-16LE");
+    }
+
+    #[test]
+    fn prefers_the_header_charset_over_meta_tags() {
+        let bytes = b"<meta charset=\"utf-8\"><p>caf\xE9";
+        let (text, encoding) = decode(bytes, Some("iso-8859-1"), "text/html");
+        assert_eq!(text, "<meta charset=\"utf-8\"><p>café");
+        assert_eq!(encoding, "windows-1252");
+
+        // Unknown labels are ignored
+        let (_, encoding) = decode(bytes, Some("no-such-charset"), "text/html");
+        assert_eq!(encoding, "UTF-8");
+    }
+
+    #[test]
+    fn uses_meta_tags_of_html_documents_only() {
+        let bytes = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1251\">\xCF\xF0\xE8";
+        let (text, encoding) = decode(bytes, None, "text/html");
+        assert!(text.ends_with("При"));
+        assert_eq!(encoding, "windows-1251");
+
+        // A meta tag wins over content that happens to be valid UTF-8, but only in HTML
+        let bytes = b"<meta charset=windows-1252><p>caf\xC3\xA9";
+        let (text, encoding) = decode(bytes, None, "text/html");
+        assert_eq!(text, "<meta charset=windows-1252><p>cafÃ©");
+        assert_eq!(encoding, "windows-1252");
+        let (text, encoding) = decode(bytes, None, "text/plain");
+        assert_eq!(text, "<meta charset=windows-1252><p>café");
+        assert_eq!(encoding, "UTF-8");
+    }
+
+    #[test]
+    fn reads_utf16_meta_tags_as_utf8() {
+        let bytes = "<meta charset=\"utf-16\"><p>café".as_bytes();
+        let (text, encoding) = decode(bytes, None, "text/html");
+        assert_eq!(text, "<meta charset=\"utf-16\"><p>café");
+        assert_eq!(encoding, "UTF-8");
+    }
+
+    #[test]
+    fn falls_back_to_utf8_or_windows_1252_without_a_declaration() {
+        let (text, encoding) = decode("<p>café</p>".as_bytes(), None, "text/html");
+        assert_eq!(text, "<p>café</p>");
+        assert_eq!(encoding, "UTF-8");
+
+        let (text, encoding) = decode(b"<p>caf\xE9 \x93quoted\x94</p>", None, "text/html");
+        assert_eq!(text, "<p>café “quoted”</p>");
+        assert_eq!(encoding, "windows-1252");
+    }
+
+    #[test]
+    fn rejects_unknown_schemes() {
+        let result = load("gopher://example.com", &MockFetcher::new());
+        assert!(matches!(result, Err(LoadError::InvalidUrl(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_files_that_are_too_large() {
+        let result = load("file:///dev/zero", &MockFetcher::new());
+        assert_eq!(result.err(), Some(LoadError::TooLarge(MAX_BODY_SIZE)));
+    }
+
+    #[test]
+    fn fetches_http_content_with_its_content_type() {
+        let fetcher = MockFetcher::new().page(
+            "https://example.com/",
+            "text/html; charset=utf-8",
+            "<h1>Hello</h1>",
+        );
+
+        let content = load("https://example.com/", &fetcher).unwrap();
+        assert_eq!(content.mime_type, "text/html");
+        assert_eq!(content.encoding.as_deref(), Some("UTF-8"));
+        assert_eq!(content.as_text(), Some("<h1>Hello</h1>"));
+        assert_eq!(content.response.status, Some((200, "OK".into())));
+        assert_eq!(fetcher.requests(), vec!["https://example.com/"]);
+    }
+
+    #[test]
+    fn follows_and_records_redirects() {
+        let fetcher = MockFetcher::new()
+            .redirect("http://example.com/", 301, "https://example.com/")
+            .redirect("https://example.com/", 302, "/login")
+            .page("https://example.com/login", "text/html", "Log in");
+
+        let content = load("http://example.com/", &fetcher).unwrap();
+        assert_eq!(content.as_text(), Some("Log in"));
+        assert_eq!(
+            content.response.final_url.as_deref(),
+            Some("https://example.com/login")
+        );
+        assert_eq!(
+            content.response.redirects,
+            vec![
+                Redirect {
+                    status: 301,
+                    url: "http://example.com/".into()
+                },
+                Redirect {
+                    status: 302,
+                    url: "https://example.com/".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_after_the_maximum_number_of_redirects() {
+        let fetcher = MockFetcher::new()
+            .redirect("https://example.com/a", 302, "/b")
+            .redirect("https://example.com/b", 302, "/a");
+        let settings = Settings {
+            max_redirects: 3,
+            ..Settings::default()
+        };
+
+        let result = load_content("https://example.com/a", false, &context(&fetcher, settings));
+        assert_eq!(result, Err(LoadError::TooManyRedirects(3)));
+        assert_eq!(fetcher.requests().len(), 4);
+    }
+
+    #[test]
+    fn fails_on_error_statuses() {
+        let fetcher = MockFetcher::new().status("https://example.com/", 404, "Not Found");
+
+        let result = load("https://example.com/", &fetcher);
+        assert_eq!(result, Err(LoadError::HttpStatus(404, "Not Found".into())));
+    }
+
+    #[test]
+    fn passes_on_connection_errors() {
+        let fetcher = MockFetcher::new().error(
+            "https://example.com/",
+            LoadError::Connection("connection refused".into()),
+        );
+
+        let result = load("https://example.com/", &fetcher);
+        assert!(matches!(result, Err(LoadError::Connection(_))));
+    }
+
+    #[test]
+    fn shows_the_source_of_fetched_pages() {
+        let fetcher = MockFetcher::new().page("https://example.com/", "text/html", "<p>Hi</p>");
+
+        let content = load("view-source:https://example.com/", &fetcher).unwrap();
+        assert_eq!(content.mime_type, "text/x-view-source-html");
+        assert_eq!(content.as_text(), Some("<p>Hi</p>"));
+        assert_eq!(
+            content.response.final_url.as_deref(),
+            Some("view-source:https://example.com/")
+        );
+
+        let content = load(" \tview-source:https://example.com/", &fetcher).unwrap();
+        assert_eq!(content.as_text(), Some("<p>Hi</p>"));
+    }
+
+    #[test]
+    fn sends_cookies_set_during_redirects() {
+        let fetcher = MockFetcher::new()
+            .response(
+                "https://example.com/login",
+                HttpResponse {
+                    status: 302,
+                    status_text: "Found".into(),
+                    headers: vec![
+                        ("Location".into(), "/home".into()),
+                        ("Set-Cookie".into(), "session=abc; Path=/".into()),
+                    ],
+                    body: vec![],
+                },
+            )
+            .page("https://example.com/home", "text/html", "Welcome");
+
+        load("https://example.com/login", &fetcher).unwrap();
+        assert_eq!(fetcher.request_header(0, "Cookie"), None);
+        assert_eq!(
+            fetcher.request_header(1, "Cookie").as_deref(),
+            Some("session=abc")
+        );
+    }
+
+    fn cached_context(fetcher: &MockFetcher) -> LoadContext {
+        let dir = std::env::temp_dir().join(format!("dive-cache-{}", Uuid::new_v4()));
+        LoadContext {
+            cache: Arc::new(Mutex::new(HttpCache::new_from_dir(dir, 1024 * 1024))),
+            ..context(fetcher, Settings::default())
+        }
+    }
+
+    #[test]
+    fn serves_fresh_responses_from_the_cache() {
+        let fetcher = MockFetcher::new().response(
+            "https://example.com/",
+            HttpResponse {
+                status: 200,
+                status_text: "OK".into(),
+                headers: vec![
+                    ("Content-Type".into(), "text/plain".into()),
+                    ("Cache-Control".into(), "max-age=3600".into()),
+                ],
+                body: b"Hello".to_vec(),
+            },
+        );
+        let context = cached_context(&fetcher);
+
+        let first = load_content("https://example.com/", false, &context).unwrap();
+        let second = load_content("https://example.com/", false, &context).unwrap();
+        assert!(!first.response.from_cache);
+        assert!(second.response.from_cache);
+        assert_eq!(second.as_text(), Some("Hello"));
+        assert_eq!(fetcher.requests().len(), 1);
+
+        // Hard loads always go to the server
+        load_content("https://example.com/", true, &context).unwrap();
+        assert_eq!(fetcher.requests().len(), 2);
+    }
+
+    #[test]
+    fn revalidates_stale_responses_in_the_cache() {
+        let fetcher = MockFetcher::new().response(
+            "https://example.com/",
+            HttpResponse {
+                status: 200,
+                status_text: "OK".into(),
+                headers: vec![
+                    ("Content-Type".into(), "text/plain".into()),
+                    ("Cache-Control".into(), "no-cache".into()),
+                    ("ETag".into(), "\"v1\"".into()),
+                ],
+                body: b"Hello".to_vec(),
+            },
+        );
+        let mut context = cached_context(&fetcher);
+        load_content("https://example.com/", false, &context).unwrap();
+
+        let not_modified = MockFetcher::new().status("https://example.com/", 304, "Not Modified");
+        context.fetcher = Arc::new(not_modified.clone());
+
+        let content = load_content("https://example.com/", false, &context).unwrap();
+        assert!(content.response.from_cache);
+        assert_eq!(content.as_text(), Some("Hello"));
+        assert_eq!(
+            not_modified.request_header(0, "If-None-Match").as_deref(),
+            Some("\"v1\"")
+        );
+    }
+
+    #[test]
+    fn sends_configured_headers() {
+        let fetcher = MockFetcher::new()
+            .page("https://api.example.com/", "text/plain", "API")
+            .page("https://other.com/", "text/plain", "Other");
+        let settings = Settings {
+            accept_language: "nl".into(),
+            domain_headers: [(
+                "example.com".to_string(),
+                [("Authorization".to_string(), "Bearer token".to_string())].into(),
+            )]
+            .into(),
+            ..Settings::default()
+        };
+        let context = context(&fetcher, settings);
+
+        load_content("https://api.example.com/", false, &context).unwrap();
+        load_content("https://other.com/", false, &context).unwrap();
+        assert_eq!(
+            fetcher.request_header(0, "Accept-Language").as_deref(),
+            Some("nl")
+        );
+        assert_eq!(
+            fetcher.request_header(0, "Authorization").as_deref(),
+            Some("Bearer token")
+        );
+        assert_eq!(fetcher.request_header(1, "Authorization"), None);
+    }
+}
@@ -1,8 +1,20 @@
 pub mod app;
 pub mod bookmark_manager;
+mod cache;
 mod command_queue;
+mod completion;
+mod cookies;
+mod fetcher;
 pub mod gosub_logger;
+mod history_manager;
+mod loader;
+pub mod render;
+pub mod search;
+mod session;
+pub mod settings;
 pub mod tab_manager;
 mod ui;
+mod url_input;
 pub mod widget_manager;
 pub mod widgets;
+mod workspace_manager;
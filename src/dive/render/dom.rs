@@ -0,0 +1,523 @@
+//! A very forgiving HTML parser. It does not try to follow the HTML5 parsing algorithm (that is
+//! what the Gosub engine is for), but it knows enough about void elements, raw text elements
+//! and implicitly closed elements to build a usable tree from real-world pages.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    /// Lowercased tag name of the element
+    pub name: String,
+    /// Attributes with lowercased names, in the order found in the source
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str, attributes: Vec<(String, String)>) -> Self {
+        Self {
+            name: name.into(),
+            attributes,
+            children: vec![],
+        }
+    }
+
+    /// Returns the value of the given attribute, if present
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the first descendant element with the given name (depth first)
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        for child in &self.children {
+            if let Node::Element(el) = child {
+                if el.name == name {
+                    return Some(el);
+                }
+                if let Some(found) = el.find(name) {
+                    return Some(found);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns all the text found inside this element
+    pub fn text(&self) -> String {
+        let mut s = String::new();
+        for child in &self.children {
+            match child {
+                Node::Text(t) => s.push_str(t),
+                Node::Element(el) => s.push_str(&el.text()),
+            }
+        }
+
+        s
+    }
+}
+
+/// Elements that never have any content or closing tag
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose content is not parsed as HTML but taken as-is up to the closing tag
+const RAW_TEXT_ELEMENTS: [&str; 6] = ["script", "style", "textarea", "title", "xmp", "plaintext"];
+
+/// Block elements that will implicitly close an open <p> element
+const CLOSES_PARAGRAPH: [&str; 25] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "div",
+    "dl",
+    "fieldset",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "ul",
+];
+
+/// Maximum number of nested open elements. Deeper elements are added as siblings instead, so
+/// pages full of unclosed tags cannot overflow the stack when the tree is walked recursively.
+const MAX_DEPTH: usize = 256;
+
+/// Parses the given HTML source into a tree. The returned element is a synthetic "#document"
+/// element that holds all top level nodes.
+pub fn parse(source: &str) -> Element {
+    let mut parser = Parser {
+        src: source,
+        pos: 0,
+        stack: vec![Element::new("#document", vec![])],
+    };
+    parser.run();
+
+    while parser.stack.len() > 1 {
+        parser.pop();
+    }
+
+    parser.stack.pop().expect("document element")
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    /// Currently open elements. The first element is always the document.
+    stack: Vec<Element>,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn run(&mut self) {
+        while self.pos < self.src.len() {
+            let rest = self.rest();
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                self.pos += match comment.find("-->") {
+                    Some(end) => 4 + end + 3,
+                    None => rest.len(),
+                };
+                continue;
+            }
+
+            if rest.starts_with("<!") || rest.starts_with("<?") {
+                self.pos += rest.find('>').map(|i| i + 1).unwrap_or(rest.len());
+                continue;
+            }
+
+            if let Some(tag) = rest.strip_prefix("</") {
+                if tag.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                    self.end_tag();
+                    continue;
+                }
+            }
+
+            if let Some(tag) = rest.strip_prefix('<') {
+                if tag.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                    self.start_tag();
+                    continue;
+                }
+            }
+
+            // Regular text up to the next tag. A lone '<' is just text.
+            let first = rest.chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+            let end = rest[first..]
+                .find('<')
+                .map(|i| i + first)
+                .unwrap_or(rest.len());
+            self.text(&rest[..end]);
+            self.pos += end;
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        let decoded = decode_entities(raw);
+        let current = self.stack.last_mut().expect("open element");
+
+        // Merge with a previous text node so the layout sees complete runs of text
+        if let Some(Node::Text(prev)) = current.children.last_mut() {
+            prev.push_str(&decoded);
+        } else {
+            current.children.push(Node::Text(decoded));
+        }
+    }
+
+    fn end_tag(&mut self) {
+        let rest = self.rest();
+        let end = rest.find('>').map(|i| i + 1).unwrap_or(rest.len());
+        let name = read_name(&rest[2..]);
+        self.pos += end;
+
+        // Only close when the element is actually open, otherwise the end tag is ignored
+        if let Some(idx) = self.stack.iter().rposition(|el| el.name == name) {
+            if idx == 0 {
+                return;
+            }
+            while self.stack.len() > idx {
+                self.pop();
+            }
+        }
+    }
+
+    fn start_tag(&mut self) {
+        let rest = self.rest();
+        let name = read_name(&rest[1..]);
+        let mut i = 1 + name.len();
+
+        let mut attributes = vec![];
+        let mut self_closing = false;
+
+        let bytes = rest.as_bytes();
+        loop {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if i >= bytes.len() {
+                break;
+            }
+            if bytes[i] == b'>' {
+                i += 1;
+                break;
+            }
+            if bytes[i] == b'/' {
+                self_closing = true;
+                i += 1;
+                continue;
+            }
+
+            // Attribute name
+            let start = i;
+            while i < bytes.len()
+                && !bytes[i].is_ascii_whitespace()
+                && !matches!(bytes[i], b'=' | b'>' | b'/')
+            {
+                i += 1;
+            }
+            let attr_name = rest[start..i].to_lowercase();
+            if attr_name.is_empty() {
+                // Something like a stray '=', skip it
+                i += 1;
+                continue;
+            }
+
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+
+            let mut value = String::new();
+            if i < bytes.len() && bytes[i] == b'=' {
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+
+                if i < bytes.len() && (bytes[i] == b'"' || bytes[i] == b'\'') {
+                    let quote = bytes[i] as char;
+                    let end = rest[i + 1..]
+                        .find(quote)
+                        .map(|e| i + 1 + e)
+                        .unwrap_or(rest.len());
+                    value = decode_entities(&rest[i + 1..end]);
+                    i = (end + 1).min(rest.len());
+                } else {
+                    let start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    value = decode_entities(&rest[start..i]);
+                }
+            }
+
+            attributes.push((attr_name, value));
+        }
+        self.pos += i;
+
+        self.close_implied(&name);
+
+        let element = Element::new(&name, attributes);
+
+        if VOID_ELEMENTS.contains(&name.as_str()) || self_closing {
+            self.stack
+                .last_mut()
+                .expect("open element")
+                .children
+                .push(Node::Element(element));
+            return;
+        }
+
+        if self.stack.len() >= MAX_DEPTH {
+            self.pop();
+        }
+        self.stack.push(element);
+
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            self.raw_text(&name);
+        }
+    }
+
+    /// Reads everything up to the closing tag of the given element as a single text node
+    fn raw_text(&mut self, name: &str) {
+        let rest = self.rest();
+        let closing = format!("</{}", name);
+        let end = rest
+            .to_ascii_lowercase()
+            .find(&closing)
+            .unwrap_or(rest.len());
+
+        let content = &rest[..end];
+        if !content.is_empty() {
+            let text = if name == "title" || name == "textarea" {
+                decode_entities(content)
+            } else {
+                content.to_string()
+            };
+            self.stack
+                .last_mut()
+                .expect("open element")
+                .children
+                .push(Node::Text(text));
+        }
+
+        self.pos += end;
+        if self.pos < self.src.len() {
+            self.end_tag();
+        } else {
+            self.pop();
+        }
+    }
+
+    /// Closes any elements that are implicitly ended by opening the given element
+    fn close_implied(&mut self, name: &str) {
+        match name {
+            "li" => self.close_until("li", &["ul", "ol", "menu"]),
+            "dt" | "dd" => {
+                self.close_until("dt", &["dl"]);
+                self.close_until("dd", &["dl"]);
+            }
+            "tr" => {
+                self.close_until("td", &["table"]);
+                self.close_until("th", &["table"]);
+                self.close_until("tr", &["table"]);
+            }
+            "td" | "th" => {
+                self.close_until("td", &["tr", "table"]);
+                self.close_until("th", &["tr", "table"]);
+            }
+            "thead" | "tbody" | "tfoot" => {
+                for el in ["td", "th", "tr", "thead", "tbody", "tfoot"] {
+                    self.close_until(el, &["table"]);
+                }
+            }
+            "option" => self.close_until("option", &["select", "datalist"]),
+            _ => {}
+        }
+
+        if CLOSES_PARAGRAPH.contains(&name) {
+            self.close_until("p", &["button", "table", "td", "th", "li"]);
+        }
+    }
+
+    /// Closes the nearest open element with the given name, unless one of the boundary elements
+    /// is found first.
+    fn close_until(&mut self, name: &str, boundaries: &[&str]) {
+        for idx in (1..self.stack.len()).rev() {
+            let el = &self.stack[idx].name;
+            if el == name {
+                while self.stack.len() > idx {
+                    self.pop();
+                }
+                return;
+            }
+            if boundaries.contains(&el.as_str()) {
+                return;
+            }
+        }
+    }
+
+    /// Pops the current element and adds it to its parent
+    fn pop(&mut self) {
+        if self.stack.len() <= 1 {
+            return;
+        }
+
+        let element = self.stack.pop().expect("open element");
+        self.stack
+            .last_mut()
+            .expect("open element")
+            .children
+            .push(Node::Element(element));
+    }
+}
+
+fn read_name(s: &str) -> String {
+    s.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == ':' || *c == '_')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Decodes character references (&amp;, &#39;, &#x27; etc) found in the given text
+pub fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(idx) = rest.find('&') {
+        out.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        let end = rest[1..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+            .map(|i| i + 1)
+            .unwrap_or(rest.len());
+        let entity = &rest[1..end];
+
+        match lookup_entity(entity) {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end..];
+                if rest.starts_with(';') {
+                    rest = &rest[1..];
+                }
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+
+    out
+}
+
+fn lookup_entity(entity: &str) -> Option<char> {
+    if let Some(num) = entity.strip_prefix('#') {
+        let code = if let Some(hex) = num.strip_prefix(['x', 'X']) {
+            u32::from_str_radix(hex, 16).ok()?
+        } else {
+            num.parse::<u32>().ok()?
+        };
+        return Some(char::from_u32(code).unwrap_or('\u{fffd}'));
+    }
+
+    let c = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "laquo" => '«',
+        "raquo" => '»',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bull" => '•',
+        "middot" => '·',
+        "euro" => '€',
+        "pound" => '£',
+        "yen" => '¥',
+        "cent" => '¢',
+        "sect" => '§',
+        "para" => '¶',
+        "deg" => '°',
+        "plusmn" => '±',
+        "times" => '×',
+        "divide" => '÷',
+        "larr" => '←',
+        "rarr" => '→',
+        "uarr" => '↑',
+        "darr" => '↓',
+        "shy" => '\u{ad}',
+        _ => return None,
+    };
+
+    Some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn depth(el: &Element) -> usize {
+        1 + el
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                Node::Element(el) => Some(depth(el)),
+                Node::Text(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn limits_the_nesting_of_unclosed_elements() {
+        let source = "<div><b>".repeat(50_000) + "deep";
+        let document = parse(&source);
+
+        assert!(depth(&document) <= MAX_DEPTH);
+        assert_eq!(document.text(), "deep");
+
+        let page = crate::dive::render::render_html(&source, 80);
+        assert!(!page.lines.is_empty());
+    }
+}
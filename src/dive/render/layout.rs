@@ -0,0 +1,709 @@
+use crate::dive::render::dom::{Element, Node};
+use crate::dive::render::table;
+use ratatui::prelude::*;
+use unicode_width::UnicodeWidthStr;
+
+/// Elements (and their content) that are never displayed
+const HIDDEN_ELEMENTS: [&str; 12] = [
+    "head", "title", "script", "style", "template", "meta", "link", "base", "noscript", "iframe",
+    "object", "svg",
+];
+
+/// Elements that only start a new line without any margin around them
+const BLOCK_ELEMENTS: [&str; 22] = [
+    "address",
+    "article",
+    "aside",
+    "caption",
+    "center",
+    "details",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "hgroup",
+    "legend",
+    "main",
+    "nav",
+    "section",
+    "summary",
+    "tbody",
+    "thead",
+];
+
+/// Tables nested deeper than this are not laid out as a grid anymore, as every level of nesting
+/// can multiply the number of times the inner cells are laid out
+const MAX_TABLE_DEPTH: usize = 8;
+
+/// Bullets used for unordered lists, depending on the nesting level
+const BULLETS: [&str; 3] = ["• ", "◦ ", "▪ "];
+
+struct List {
+    ordered: bool,
+    next: i64,
+}
+
+/// Lays out a tree of nodes into styled lines for a given width. Text is wrapped on word
+/// boundaries, and block elements get their own lines, indentation and vertical margins.
+pub struct Layout {
+    width: usize,
+    lines: Vec<Line<'static>>,
+    /// Spans of the line that is currently being built
+    spans: Vec<Span<'static>>,
+    /// Number of columns used on the current line, including the prefix
+    col: usize,
+    /// True when the current line has been started (its prefix has been added)
+    in_line: bool,
+    /// True when the current line holds any content besides its prefix
+    has_text: bool,
+    /// Whitespace has been seen that should be output before the next word
+    space: bool,
+    /// Number of blank lines that must be added before the next content
+    margin: usize,
+    /// Number of blank lines currently at the end of the output
+    blanks: usize,
+    /// Indentation prefixes (for lists, blockquotes etc) for every line
+    prefixes: Vec<String>,
+    /// Marker (like a list bullet) that replaces the prefix at the given depth on the next line
+    marker: Option<(usize, String)>,
+    styles: Vec<Style>,
+    lists: Vec<List>,
+    /// Depth of <pre> elements we are in
+    pre: usize,
+    /// Hrefs of all links found, in order of their number
+    links: Vec<String>,
+    /// Number of links that came before this layout (for table cells that are laid out on
+    /// their own)
+    first_link: usize,
+    /// Number of tables this layout is nested in
+    tables: usize,
+}
+
+impl Layout {
+    pub fn new(width: usize) -> Self {
+        Self {
+            width: width.max(1),
+            lines: vec![],
+            spans: vec![],
+            col: 0,
+            in_line: false,
+            has_text: false,
+            space: false,
+            margin: 0,
+            blanks: 0,
+            prefixes: vec![],
+            marker: None,
+            styles: vec![Style::default()],
+            lists: vec![],
+            pre: 0,
+            links: vec![],
+            first_link: 0,
+            tables: 0,
+        }
+    }
+
+    /// Lays out the given element and all its children
+    pub fn element(&mut self, el: &Element) {
+        let name = el.name.as_str();
+
+        if HIDDEN_ELEMENTS.contains(&name) {
+            return;
+        }
+
+        match name {
+            "h1" => self.block(1, |l| {
+                l.styled(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                    |l| l.children(el),
+                )
+            }),
+            "h2" => self.block(1, |l| {
+                l.styled(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                    |l| l.children(el),
+                )
+            }),
+            "h3" => self.block(1, |l| {
+                l.styled(
+                    Style::default()
+                        .fg(Color::LightCyan)
+                        .add_modifier(Modifier::BOLD),
+                    |l| l.children(el),
+                )
+            }),
+            "h4" | "h5" | "h6" => self.block(1, |l| {
+                l.styled(
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                    |l| l.children(el),
+                )
+            }),
+            "p" => self.block(1, |l| l.children(el)),
+            "br" => {
+                self.start_line();
+                self.end_line();
+            }
+            "hr" => self.block(1, |l| {
+                l.start_line();
+                let len = l.width.saturating_sub(l.col);
+                l.spans.push(Span::styled(
+                    "─".repeat(len),
+                    Style::default().fg(Color::Gray),
+                ));
+                l.end_line();
+            }),
+            "pre" | "listing" | "xmp" | "plaintext" => self.block(1, |l| {
+                l.pre += 1;
+                l.styled(Style::default().fg(Color::LightGreen), |l| {
+                    for (idx, child) in el.children.iter().enumerate() {
+                        match child {
+                            // A newline directly after the opening tag is ignored
+                            Node::Text(t) if idx == 0 => {
+                                l.text(t.strip_prefix('\n').unwrap_or(t));
+                            }
+                            _ => l.node(child),
+                        }
+                    }
+                });
+                l.pre -= 1;
+            }),
+            "blockquote" => self.block(1, |l| l.indented("│ ", |l| l.children(el))),
+            "ul" | "ol" | "menu" | "dir" => {
+                let margin = if self.lists.is_empty() { 1 } else { 0 };
+                let next = el
+                    .attr("start")
+                    .and_then(|s| s.trim().parse().ok())
+                    .unwrap_or(1);
+
+                self.lists.push(List {
+                    ordered: name == "ol",
+                    next,
+                });
+                self.block(margin, |l| l.children(el));
+                self.lists.pop();
+            }
+            "li" => {
+                let depth = self.lists.len();
+                let marker = match self.lists.last_mut() {
+                    Some(list) if list.ordered => {
+                        list.next += 1;
+                        format!("{}. ", list.next - 1)
+                    }
+                    _ => BULLETS[depth.saturating_sub(1) % BULLETS.len()].to_string(),
+                };
+
+                self.block(0, |l| {
+                    let indent = " ".repeat(marker.width());
+                    l.marker = Some((l.prefixes.len(), marker));
+                    l.indented(&indent, |l| l.children(el));
+                    l.marker = None;
+                });
+            }
+            "dt" => self.block(0, |l| {
+                l.styled(Style::default().add_modifier(Modifier::BOLD), |l| {
+                    l.children(el)
+                })
+            }),
+            "dd" => self.block(0, |l| l.indented("    ", |l| l.children(el))),
+            "table" if self.tables >= MAX_TABLE_DEPTH => self.block(1, |l| l.children(el)),
+            "table" => self.block(1, |l| l.table(el)),
+            // Rows and cells outside of a table
+            "tr" => self.block(0, |l| l.children(el)),
+            "td" | "th" => {
+                // Without a table layout, cells are simply separated by whitespace
+                self.space = true;
+                if name == "th" {
+                    self.styled(Style::default().add_modifier(Modifier::BOLD), |l| {
+                        l.children(el)
+                    });
+                } else {
+                    self.children(el);
+                }
+                self.space = true;
+            }
+            "a" => match el.attr("href").map(|href| href.trim()) {
+                Some(href) if !href.is_empty() => {
+                    self.links.push(href.to_string());
+                    let number = self.first_link + self.links.len();
+
+                    // The link number is placed in front of the link, lynx-style
+                    self.styled(
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                        |l| l.word(&format!("[{}]", number)),
+                    );
+                    self.styled(
+                        Style::default()
+                            .fg(Color::LightCyan)
+                            .add_modifier(Modifier::UNDERLINED),
+                        |l| l.children(el),
+                    );
+                }
+                // Anchors without a href are not links
+                _ => self.children(el),
+            },
+            "b" | "strong" => self.styled(Style::default().add_modifier(Modifier::BOLD), |l| {
+                l.children(el)
+            }),
+            "i" | "em" | "cite" | "var" | "dfn" => self
+                .styled(Style::default().add_modifier(Modifier::ITALIC), |l| {
+                    l.children(el)
+                }),
+            "u" | "ins" => self.styled(Style::default().add_modifier(Modifier::UNDERLINED), |l| {
+                l.children(el)
+            }),
+            "s" | "strike" | "del" => self
+                .styled(Style::default().add_modifier(Modifier::CROSSED_OUT), |l| {
+                    l.children(el)
+                }),
+            "code" | "kbd" | "samp" | "tt" => {
+                self.styled(Style::default().fg(Color::LightGreen), |l| l.children(el))
+            }
+            "mark" => self.styled(Style::default().fg(Color::Black).bg(Color::Yellow), |l| {
+                l.children(el)
+            }),
+            "q" => {
+                self.text("“");
+                self.children(el);
+                self.text("”");
+            }
+            "img" => {
+                let alt = el.attr("alt").map(|a| a.trim()).unwrap_or("");
+                let label = if alt.is_empty() { "image" } else { alt };
+                self.styled(Style::default().add_modifier(Modifier::DIM), |l| {
+                    l.text(&format!("[{}]", label))
+                });
+            }
+            "input" => self.input(el),
+            "button" => {
+                self.text("[");
+                self.children(el);
+                self.text("]");
+            }
+            "select" => {
+                let selected = select_label(el);
+                self.text(&format!("[{} ▾]", selected.trim()));
+            }
+            "textarea" => self.text(&format!("[{}]", el.text().trim())),
+            _ if BLOCK_ELEMENTS.contains(&name) => self.block(0, |l| l.children(el)),
+            // Any other element is treated as an inline element, so at least its content is shown
+            _ => self.children(el),
+        }
+    }
+
+    fn table(&mut self, el: &Element) {
+        if let Some(Node::Element(caption)) = el
+            .children
+            .iter()
+            .find(|child| matches!(child, Node::Element(c) if c.name == "caption"))
+        {
+            self.block(0, |l| {
+                l.styled(Style::default().add_modifier(Modifier::ITALIC), |l| {
+                    l.children(caption)
+                })
+            });
+        }
+
+        let width = self.width.saturating_sub(self.prefixes.concat().width());
+        let first_link = self.first_link + self.links.len();
+
+        let (lines, links) = table::layout(el, width, |cell, width, cell_first_link| {
+            let mut layout = Layout::new(width);
+            layout.first_link = first_link + cell_first_link;
+            layout.tables = self.tables + 1;
+            if cell.name == "th" {
+                layout.styled(Style::default().add_modifier(Modifier::BOLD), |l| {
+                    l.children(cell)
+                });
+            } else {
+                layout.children(cell);
+            }
+
+            layout.finish()
+        });
+        self.links.extend(links);
+
+        for line in lines {
+            self.start_line();
+            self.spans.extend(line.spans);
+            self.has_text = true;
+            self.end_line();
+        }
+    }
+
+    /// Returns the laid out lines, and the hrefs of all the links found
+    pub fn finish(mut self) -> (Vec<Line<'static>>, Vec<String>) {
+        self.end_line();
+        (self.lines, self.links)
+    }
+
+    fn node(&mut self, node: &Node) {
+        match node {
+            Node::Text(t) => self.text(t),
+            Node::Element(el) => self.element(el),
+        }
+    }
+
+    fn children(&mut self, el: &Element) {
+        for child in &el.children {
+            self.node(child);
+        }
+    }
+
+    fn style(&self) -> Style {
+        *self.styles.last().expect("style")
+    }
+
+    /// Runs the closure with the given style patched onto the current style
+    fn styled<F: FnOnce(&mut Self)>(&mut self, style: Style, f: F) {
+        self.styles.push(self.style().patch(style));
+        f(self);
+        self.styles.pop();
+    }
+
+    /// Runs the closure with an additional indentation prefix for every line
+    fn indented<F: FnOnce(&mut Self)>(&mut self, prefix: &str, f: F) {
+        // Margins before the indented content should not get the new prefix
+        self.add_margin();
+        self.prefixes.push(prefix.into());
+        f(self);
+        self.end_line();
+        self.prefixes.pop();
+    }
+
+    /// Runs the closure as a block element, separated by the given number of blank lines from
+    /// any surrounding content.
+    fn block<F: FnOnce(&mut Self)>(&mut self, margin: usize, f: F) {
+        self.end_block(margin);
+        f(self);
+        self.end_block(margin);
+    }
+
+    fn end_block(&mut self, margin: usize) {
+        self.end_line();
+        self.space = false;
+        self.margin = self.margin.max(margin);
+    }
+
+    /// Starts a new line when needed, by adding any pending margin and the line prefix
+    fn start_line(&mut self) {
+        if self.in_line {
+            return;
+        }
+
+        self.add_margin();
+
+        let mut prefix = String::new();
+        for (depth, p) in self.prefixes.iter().enumerate() {
+            match &self.marker {
+                Some((marker_depth, marker)) if *marker_depth == depth => prefix.push_str(marker),
+                _ => prefix.push_str(p),
+            }
+        }
+        self.marker = None;
+
+        self.col = prefix.width();
+        if !prefix.is_empty() {
+            self.spans.push(Span::raw(prefix));
+        }
+        self.in_line = true;
+        self.has_text = false;
+    }
+
+    /// Adds any pending margin as blank lines, collapsing it with blank lines already present
+    fn add_margin(&mut self) {
+        if !self.lines.is_empty() {
+            let blank = self.prefixes.concat().trim_end().to_string();
+            while self.blanks < self.margin {
+                self.lines.push(Line::from(blank.clone()));
+                self.blanks += 1;
+            }
+        }
+        self.margin = 0;
+    }
+
+    fn end_line(&mut self) {
+        if !self.in_line {
+            return;
+        }
+
+        self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        self.blanks = 0;
+        self.in_line = false;
+        self.has_text = false;
+        self.col = 0;
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.pre > 0 {
+            for (idx, line) in text.split('\n').enumerate() {
+                if idx > 0 {
+                    self.start_line();
+                    self.end_line();
+                }
+                if !line.is_empty() {
+                    self.start_line();
+                    self.push(line.replace('\t', "    "));
+                }
+            }
+            return;
+        }
+
+        if text.starts_with(|c: char| c.is_ascii_whitespace()) {
+            self.space = true;
+        }
+
+        for (idx, word) in text
+            .split(|c: char| c.is_ascii_whitespace())
+            .filter(|w| !w.is_empty())
+            .enumerate()
+        {
+            if idx > 0 {
+                self.space = true;
+            }
+            self.word(&word.replace('\u{a0}', " "));
+        }
+
+        if text.ends_with(|c: char| c.is_ascii_whitespace()) {
+            self.space = true;
+        }
+    }
+
+    /// Adds a single word, wrapping to the next line when it does not fit anymore
+    fn word(&mut self, word: &str) {
+        let space = if self.has_text && self.space { 1 } else { 0 };
+        if self.has_text && self.col + space + word.width() > self.width {
+            self.end_line();
+        }
+
+        self.start_line();
+        if self.has_text && self.space {
+            self.spans.push(Span::raw(" "));
+            self.col += 1;
+        }
+        self.space = false;
+
+        // Words that are longer than a complete line are broken up
+        let mut rest = word;
+        while self.col + rest.width() > self.width && !rest.is_empty() {
+            let available = self.width.saturating_sub(self.col).max(1);
+            let mut split = 0;
+            let mut used = 0;
+            for (idx, c) in rest.char_indices() {
+                let w = c.to_string().width();
+                if used + w > available && split > 0 {
+                    break;
+                }
+                used += w;
+                split = idx + c.len_utf8();
+            }
+
+            self.push(rest[..split].to_string());
+            rest = &rest[split..];
+            self.end_line();
+            self.start_line();
+        }
+
+        if !rest.is_empty() {
+            self.push(rest.to_string());
+        }
+    }
+
+    /// Adds text to the current line without any wrapping
+    fn push(&mut self, text: String) {
+        self.col += text.width();
+        self.spans.push(Span::styled(text, self.style()));
+        self.has_text = true;
+    }
+
+    fn input(&mut self, el: &Element) {
+        let value = el.attr("value").unwrap_or("");
+        let label = match el.attr("type").unwrap_or("text").to_lowercase().as_str() {
+            "hidden" => return,
+            "submit" | "button" | "reset" => {
+                let value = if value.is_empty() { "Submit" } else { value };
+                format!("[{}]", value)
+            }
+            "checkbox" => {
+                if el.attr("checked").is_some() {
+                    "[x]".into()
+                } else {
+                    "[ ]".into()
+                }
+            }
+            "radio" => {
+                if el.attr("checked").is_some() {
+                    "(*)".into()
+                } else {
+                    "( )".into()
+                }
+            }
+            "password" => format!("[{:_<12}]", "*".repeat(value.chars().count())),
+            _ => {
+                let value = if value.is_empty() {
+                    el.attr("placeholder").unwrap_or("")
+                } else {
+                    value
+                };
+                format!("[{:_<12}]", value)
+            }
+        };
+
+        self.word(&label);
+    }
+}
+
+/// Returns the label of the selected option of a <select> element
+fn select_label(el: &Element) -> String {
+    let mut first = None;
+
+    let mut stack = vec![el];
+    while let Some(el) = stack.pop() {
+        for child in el.children.iter().rev() {
+            if let Node::Element(child) = child {
+                if child.name == "option" {
+                    if child.attr("selected").is_some() {
+                        return child.text();
+                    }
+                    first = Some(child.text());
+                } else {
+                    stack.push(child);
+                }
+            }
+        }
+    }
+
+    first.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dive::render::render_html;
+    use ratatui::prelude::*;
+
+    fn texts(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the span holding the given text
+    fn span<'a>(lines: &'a [Line], text: &str) -> &'a Span<'a> {
+        lines
+            .iter()
+            .flat_map(|line| &line.spans)
+            .find(|span| span.content == text)
+            .expect("span")
+    }
+
+    #[test]
+    fn lays_out_headings_and_paragraphs() {
+        let page = render_html("<h1>Title</h1><p>First</p><p>Second</p>", 40);
+        assert_eq!(texts(&page.lines), vec!["Title", "", "First", "", "Second"]);
+
+        let style = span(&page.lines, "Title").style;
+        assert!(style.add_modifier.contains(Modifier::BOLD));
+        assert!(style.add_modifier.contains(Modifier::UNDERLINED));
+        assert_eq!(span(&page.lines, "First").style, Style::default());
+    }
+
+    #[test]
+    fn styles_inline_elements() {
+        let page = render_html("<p>a <b>bold</b> and <em>italic</em> word</p>", 40);
+        assert_eq!(texts(&page.lines), vec!["a bold and italic word"]);
+        assert!(span(&page.lines, "bold")
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert!(span(&page.lines, "italic")
+            .style
+            .add_modifier
+            .contains(Modifier::ITALIC));
+    }
+
+    #[test]
+    fn indents_lists_with_bullets_and_numbers() {
+        let page = render_html(
+            "<ul><li>One</li><li>Two<ul><li>Nested</li></ul></li></ul>\
+             <ol start=3><li>Three</li><li>Four</li></ol>",
+            40,
+        );
+        assert_eq!(
+            texts(&page.lines),
+            vec!["• One", "• Two", "  ◦ Nested", "", "3. Three", "4. Four"]
+        );
+    }
+
+    #[test]
+    fn keeps_preformatted_text_as_is() {
+        let page = render_html(
+            "<pre>\nfn main() {\n    println!(\"a long line that does not fit\");\n}</pre>",
+            20,
+        );
+        assert_eq!(
+            texts(&page.lines),
+            vec![
+                "fn main() {",
+                "    println!(\"a long line that does not fit\");",
+                "}"
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_links_in_front_of_their_text() {
+        let page = render_html(
+            "<p>See <a href=\"/docs\">the docs</a> or <a>no link</a> or \
+             <a href=\"https://example.com/\">example</a></p>",
+            80,
+        );
+        assert_eq!(
+            texts(&page.lines),
+            vec!["See [1]the docs or no link or [2]example"]
+        );
+        assert_eq!(page.links, vec!["/docs", "https://example.com/"]);
+        assert!(span(&page.lines, "[1]")
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert!(span(&page.lines, "docs")
+            .style
+            .add_modifier
+            .contains(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn wraps_text_at_the_width() {
+        let page = render_html("<p>one two three four five</p>", 9);
+        assert_eq!(texts(&page.lines), vec!["one two", "three", "four five"]);
+
+        // Words longer than a line are broken up
+        let page = render_html("<p>abcdefghijkl</p>", 5);
+        assert_eq!(texts(&page.lines), vec!["abcde", "fghij", "kl"]);
+    }
+
+    #[test]
+    fn shows_the_content_of_unknown_elements() {
+        let page = render_html(
+            "<custom-card>Hello <blink>world</blink></custom-card><script>hidden()</script>",
+            40,
+        );
+        assert_eq!(texts(&page.lines), vec!["Hello world"]);
+    }
+}
@@ -0,0 +1,192 @@
+use ratatui::text::Line;
+use unicode_width::UnicodeWidthChar;
+use url::Url;
+
+pub mod dom;
+pub mod layout;
+pub mod registry;
+pub mod source;
+mod table;
+
+/// A document that has been laid out for a specific width
+pub struct RenderedPage {
+    /// Width the page has been laid out for
+    pub width: usize,
+    pub lines: Vec<Line<'static>>,
+    /// Hrefs of the links on the page. Link number N is found at index N-1.
+    pub links: Vec<String>,
+    /// Url from the <base> element of the document, if any
+    pub base: Option<String>,
+}
+
+impl RenderedPage {
+    /// Returns the absolute url of the given link number, resolved against the base url of
+    /// the document, or the given url of the page itself.
+    pub fn link_url(&self, number: usize, page_url: &str) -> Option<String> {
+        let href = self.links.get(number.checked_sub(1)?)?;
+
+        // The base url can itself be relative to the url of the page
+        let base = match &self.base {
+            Some(base) => Url::parse(page_url)
+                .and_then(|page_url| page_url.join(base))
+                .or_else(|_| Url::parse(base)),
+            None => Url::parse(page_url),
+        };
+
+        match base.and_then(|base| base.join(href)) {
+            Ok(url) => Some(url.to_string()),
+            // Pages without a valid url can only have absolute links
+            Err(_) => Url::parse(href).ok().map(|url| url.to_string()),
+        }
+    }
+}
+
+/// Parses the given HTML source and lays it out into lines of styled text
+pub fn render_html(source: &str, width: usize) -> RenderedPage {
+    let document = dom::parse(source);
+
+    let base = document
+        .find("base")
+        .and_then(|base| base.attr("href"))
+        .map(|href| href.trim().to_string());
+
+    let mut layout = layout::Layout::new(width);
+    layout.element(&document);
+    let (lines, links) = layout.finish();
+
+    RenderedPage {
+        width,
+        lines,
+        links,
+        base,
+    }
+}
+
+/// Lays out plain text as is, only wrapping lines that don't fit the given width
+pub fn render_text(source: &str, width: usize) -> RenderedPage {
+    let mut lines = vec![];
+
+    for source_line in source.lines() {
+        let mut line = String::new();
+        let mut col = 0;
+        for c in source_line.chars() {
+            // Tabs are expanded to the next tab stop, other control characters are dropped
+            if c == '\t' {
+                let spaces = 8 - col % 8;
+                line.push_str(&" ".repeat(spaces));
+                col += spaces;
+                continue;
+            }
+            let Some(char_width) = c.width() else {
+                continue;
+            };
+
+            if col > 0 && col + char_width > width {
+                lines.push(Line::from(std::mem::take(&mut line)));
+                col = 0;
+            }
+            line.push(c);
+            col += char_width;
+        }
+        lines.push(Line::from(line));
+    }
+
+    RenderedPage {
+        width,
+        lines,
+        links: vec![],
+        base: None,
+    }
+}
+
+/// Pretty prints a JSON document. Content that is not valid JSON is shown as it is.
+pub fn render_json(source: &str, width: usize) -> RenderedPage {
+    match serde_json::from_str::<serde_json::Value>(source)
+        .and_then(|value| serde_json::to_string_pretty(&value))
+    {
+        Ok(pretty) => render_text(&pretty, width),
+        Err(_) => render_text(source, width),
+    }
+}
+
+/// Converts a Markdown document to HTML, and lays that out like any other HTML page
+pub fn render_markdown(source: &str, width: usize) -> RenderedPage {
+    let options =
+        pulldown_cmark::Options::ENABLE_TABLES | pulldown_cmark::Options::ENABLE_STRIKETHROUGH;
+    let parser = pulldown_cmark::Parser::new_ext(source, options);
+
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+
+    render_html(&html, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_URL: &str = "https://example.com/blog/post.html";
+
+    #[test]
+    fn resolves_links_against_the_page_url() {
+        let page = render_html(
+            "<a href=\"other.html\">a</a> <a href=\"/about\">b</a> \
+             <a href=\"https://rust-lang.org/\">c</a>",
+            80,
+        );
+
+        assert_eq!(
+            page.link_url(1, PAGE_URL).as_deref(),
+            Some("https://example.com/blog/other.html")
+        );
+        assert_eq!(
+            page.link_url(2, PAGE_URL).as_deref(),
+            Some("https://example.com/about")
+        );
+        assert_eq!(
+            page.link_url(3, PAGE_URL).as_deref(),
+            Some("https://rust-lang.org/")
+        );
+        assert_eq!(page.link_url(0, PAGE_URL), None);
+        assert_eq!(page.link_url(4, PAGE_URL), None);
+    }
+
+    #[test]
+    fn resolves_links_against_the_base_url() {
+        let page = render_html(
+            "<base href=\"/docs/\"><a href=\"intro.html\">a</a> \
+             <a href=\"https://rust-lang.org/\">b</a>",
+            80,
+        );
+        assert_eq!(
+            page.link_url(1, PAGE_URL).as_deref(),
+            Some("https://example.com/docs/intro.html")
+        );
+        assert_eq!(
+            page.link_url(2, PAGE_URL).as_deref(),
+            Some("https://rust-lang.org/")
+        );
+
+        let page = render_html(
+            "<base href=\"https://cdn.example.org/v2/\"><a href=\"intro.html\">a</a>",
+            80,
+        );
+        assert_eq!(
+            page.link_url(1, PAGE_URL).as_deref(),
+            Some("https://cdn.example.org/v2/intro.html")
+        );
+    }
+
+    #[test]
+    fn resolves_only_absolute_links_without_a_valid_page_url() {
+        let page = render_html(
+            "<a href=\"intro.html\">a</a> <a href=\"https://rust-lang.org/\">b</a>",
+            80,
+        );
+        assert_eq!(page.link_url(1, "not a url"), None);
+        assert_eq!(
+            page.link_url(2, "not a url").as_deref(),
+            Some("https://rust-lang.org/")
+        );
+    }
+}
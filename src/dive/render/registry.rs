@@ -0,0 +1,188 @@
+use crate::dive::loader::{format_size, Content};
+use crate::dive::render::source::{render_source, Language};
+use crate::dive::render::{render_html, render_json, render_markdown, render_text, RenderedPage};
+use ratatui::prelude::{Color, Line, Span, Stylize};
+use std::borrow::Cow;
+
+/// Turns content of a specific type into lines of styled text
+pub trait Renderer {
+    fn render(&self, content: &Content, width: usize) -> RenderedPage;
+}
+
+/// Finds the renderer for content by its MIME type
+pub struct RendererRegistry {
+    /// Renderers by MIME type pattern, which is either an exact type ("text/html"), all
+    /// subtypes of a type ("text/*") or a structured syntax suffix ("*+json").
+    renderers: Vec<(String, Box<dyn Renderer>)>,
+    /// Renderer for all content that has no registered renderer
+    fallback: Box<dyn Renderer>,
+}
+
+impl RendererRegistry {
+    pub fn new(fallback: Box<dyn Renderer>) -> Self {
+        Self {
+            renderers: vec![],
+            fallback,
+        }
+    }
+
+    /// Registers a renderer for the given MIME type pattern. Renderers for exact types take
+    /// precedence over wildcard patterns, and longer patterns over shorter ones, regardless of
+    /// the order of registration.
+    pub fn register(&mut self, pattern: &str, renderer: Box<dyn Renderer>) {
+        let pattern = pattern.to_lowercase();
+        self.renderers.retain(|(p, _)| *p != pattern);
+        self.renderers.push((pattern, renderer));
+    }
+
+    pub fn find(&self, mime_type: &str) -> &dyn Renderer {
+        let mime_type = mime_type.to_lowercase();
+
+        let exact = self.renderers.iter().find(|(p, _)| *p == mime_type);
+        let wildcard = || {
+            self.renderers
+                .iter()
+                .filter(|(p, _)| {
+                    if let Some(suffix) = p.strip_prefix('*') {
+                        mime_type.ends_with(suffix)
+                    } else if let Some(prefix) = p.strip_suffix('*') {
+                        mime_type.starts_with(prefix)
+                    } else {
+                        false
+                    }
+                })
+                .max_by_key(|(p, _)| p.len())
+        };
+
+        match exact.or_else(wildcard) {
+            Some((_, renderer)) => renderer.as_ref(),
+            None => self.fallback.as_ref(),
+        }
+    }
+
+    pub fn render(&self, content: &Content, width: usize) -> RenderedPage {
+        self.find(&content.mime_type).render(content, width)
+    }
+}
+
+impl Default for RendererRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new(Box::new(BinaryRenderer));
+        registry.register("text/html", Box::new(HtmlRenderer));
+        registry.register("application/xhtml+xml", Box::new(HtmlRenderer));
+        registry.register("text/markdown", Box::new(MarkdownRenderer));
+        registry.register("application/json", Box::new(JsonRenderer));
+        registry.register("*+json", Box::new(JsonRenderer));
+        registry.register("text/*", Box::new(TextRenderer));
+        registry.register("application/xml", Box::new(TextRenderer));
+        registry.register("application/javascript", Box::new(TextRenderer));
+        registry.register("text/x-view-source*", Box::new(SourceRenderer));
+        registry
+    }
+}
+
+/// Returns the content as text, even when it has been loaded as binary
+fn text(content: &Content) -> Cow<'_, str> {
+    match content.as_text() {
+        Some(text) => Cow::Borrowed(text),
+        None => String::from_utf8_lossy(content.as_bytes()),
+    }
+}
+
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, content: &Content, width: usize) -> RenderedPage {
+        render_html(&text(content), width)
+    }
+}
+
+pub struct TextRenderer;
+
+impl Renderer for TextRenderer {
+    fn render(&self, content: &Content, width: usize) -> RenderedPage {
+        render_text(&text(content), width)
+    }
+}
+
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn render(&self, content: &Content, width: usize) -> RenderedPage {
+        render_json(&text(content), width)
+    }
+}
+
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn render(&self, content: &Content, width: usize) -> RenderedPage {
+        render_markdown(&text(content), width)
+    }
+}
+
+/// Shows the source of a page with line numbers and syntax highlighting. The language is
+/// taken from the MIME type, like "text/x-view-source-css".
+pub struct SourceRenderer;
+
+impl Renderer for SourceRenderer {
+    fn render(&self, content: &Content, width: usize) -> RenderedPage {
+        let language = match content.mime_type.strip_prefix("text/x-view-source-") {
+            Some("html") => Language::Html,
+            Some("css") => Language::Css,
+            Some("javascript") => Language::JavaScript,
+            _ => Language::Plain,
+        };
+
+        render_source(&text(content), language, width)
+    }
+}
+
+/// Shows a short description of content we cannot display, and how to save it instead
+pub struct BinaryRenderer;
+
+impl Renderer for BinaryRenderer {
+    fn render(&self, content: &Content, width: usize) -> RenderedPage {
+        let lines = vec![
+            Line::from(""),
+            Line::from(Span::raw("Binary content").bold()),
+            Line::from(""),
+            Line::from(format!("Type: {}", content.mime_type)),
+            Line::from(format!(
+                "Size: {}",
+                format_size(content.as_bytes().len() as u64)
+            )),
+            Line::from(""),
+            Line::from(vec![
+                Span::raw("Press "),
+                Span::raw("s").bold().fg(Color::Yellow),
+                Span::raw(" to save it to disk."),
+            ]),
+        ];
+
+        RenderedPage {
+            width,
+            lines,
+            links: vec![],
+            base: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registering_a_pattern_again_replaces_it_regardless_of_case() {
+        let mut registry = RendererRegistry::default();
+        let count = registry.renderers.len();
+
+        registry.register("Text/HTML", Box::new(TextRenderer));
+        assert_eq!(registry.renderers.len(), count);
+
+        let content = Content::plain("<b>bold</b>");
+        let page = registry.find("text/html").render(&content, 80);
+        assert_eq!(page.lines[0].spans[0].content, "<b>bold</b>");
+    }
+}
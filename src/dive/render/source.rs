@@ -0,0 +1,500 @@
+//! Syntax highlighting of page sources, as shown on view-source: pages. The highlighting is
+//! deliberately simple: it only has to make raw markup readable, not to validate it.
+
+use crate::dive::render::RenderedPage;
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+use unicode_width::UnicodeWidthChar;
+
+/// Languages we know how to highlight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Language {
+    Html,
+    Css,
+    JavaScript,
+    Plain,
+}
+
+const JS_KEYWORDS: &[&str] = &[
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "let",
+    "new",
+    "null",
+    "of",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "yield",
+];
+
+fn comment() -> Style {
+    Style::default()
+        .fg(Color::Gray)
+        .add_modifier(Modifier::ITALIC)
+}
+
+fn tag() -> Style {
+    Style::default()
+        .fg(Color::LightCyan)
+        .add_modifier(Modifier::BOLD)
+}
+
+fn attribute() -> Style {
+    Style::default().fg(Color::Yellow)
+}
+
+fn string() -> Style {
+    Style::default().fg(Color::LightGreen)
+}
+
+fn literal() -> Style {
+    Style::default().fg(Color::LightMagenta)
+}
+
+fn keyword() -> Style {
+    Style::default()
+        .fg(Color::LightMagenta)
+        .add_modifier(Modifier::BOLD)
+}
+
+fn gutter() -> Style {
+    Style::default().fg(Color::Gray)
+}
+
+/// Collects the highlighted pieces of a source, merging pieces with the same style
+#[derive(Default)]
+struct Highlighter {
+    spans: Vec<(String, Style)>,
+}
+
+impl Highlighter {
+    fn push(&mut self, text: &str, style: Style) {
+        if text.is_empty() {
+            return;
+        }
+
+        match self.spans.last_mut() {
+            Some((last, last_style)) if *last_style == style => last.push_str(text),
+            _ => self.spans.push((text.to_string(), style)),
+        }
+    }
+
+    fn html(&mut self, source: &str) {
+        let mut rest = source;
+
+        while !rest.is_empty() {
+            let len = if rest.starts_with("<!--") {
+                let len = find_end(rest, "-->");
+                self.push(&rest[..len], comment());
+                len
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                let len = find_end(rest, ">");
+                self.push(&rest[..len], comment());
+                len
+            } else if is_tag_start(rest) {
+                let len = tag_length(rest);
+                let name = self.tag(&rest[..len]);
+
+                // Scripts and styles are highlighted in their own language up to their end tag
+                let language = match name.as_str() {
+                    "script" => Language::JavaScript,
+                    "style" => Language::Css,
+                    _ => Language::Plain,
+                };
+                if language != Language::Plain {
+                    let body = &rest[len..];
+                    let close = format!("</{}", name);
+                    let end = body.to_ascii_lowercase().find(&close).unwrap_or(body.len());
+                    self.language(&body[..end], language);
+                    rest = &body[end..];
+                    continue;
+                }
+                len
+            } else if rest.starts_with('&') {
+                let len = entity_length(rest);
+                self.push(&rest[..len], literal());
+                len
+            } else {
+                // Text up to the next tag or entity. A lone '<' is just text.
+                let first = rest.chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+                let len = rest[first..]
+                    .find(['<', '&'])
+                    .map(|idx| idx + first)
+                    .unwrap_or(rest.len());
+                self.push(&rest[..len], Style::default());
+                len
+            };
+
+            rest = &rest[len..];
+        }
+    }
+
+    /// Highlights a single tag, like `<a href="/">`. Returns the lowercased name of the tag
+    /// when it is a start tag, or an empty string for end tags.
+    fn tag(&mut self, source: &str) -> String {
+        let start = if source.starts_with("</") { 2 } else { 1 };
+        let name_len = source[start..]
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .unwrap_or(source.len() - start);
+        let name = &source[start..start + name_len];
+
+        self.push(&source[..start + name_len], tag());
+
+        let mut rest = &source[start + name_len..];
+        while let Some(c) = rest.chars().next() {
+            let len = if c.is_whitespace() {
+                let len = rest
+                    .find(|c: char| !c.is_whitespace())
+                    .unwrap_or(rest.len());
+                self.push(&rest[..len], Style::default());
+                len
+            } else if c == '/' || c == '>' {
+                self.push(&rest[..1], tag());
+                1
+            } else if c == '=' {
+                self.push(&rest[..1], Style::default());
+                1
+            } else if c == '"' || c == '\'' {
+                let len = rest[1..].find(c).map(|idx| idx + 2).unwrap_or(rest.len());
+                self.push(&rest[..len], string());
+                len
+            } else {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+                    .unwrap_or(rest.len())
+                    .max(c.len_utf8());
+                self.push(&rest[..len], attribute());
+                len
+            };
+
+            rest = &rest[len..];
+        }
+
+        if start == 1 {
+            name.to_ascii_lowercase()
+        } else {
+            String::new()
+        }
+    }
+
+    fn css(&mut self, source: &str) {
+        let mut rest = source;
+        let mut depth: usize = 0;
+        // Set after the colon of a declaration, until its semicolon
+        let mut in_value = false;
+
+        while let Some(c) = rest.chars().next() {
+            let len = if rest.starts_with("/*") {
+                let len = find_end(rest, "*/");
+                self.push(&rest[..len], comment());
+                len
+            } else if c == '"' || c == '\'' {
+                let len = string_length(rest, c);
+                self.push(&rest[..len], string());
+                len
+            } else if c == '@' {
+                let len = word_length(&rest[1..]) + 1;
+                self.push(&rest[..len], keyword());
+                len
+            } else if c.is_ascii_digit() && in_value {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '%'))
+                    .unwrap_or(rest.len());
+                self.push(&rest[..len], literal());
+                len
+            } else if matches!(c, '{' | '}' | ':' | ';') {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth = depth.saturating_sub(1),
+                    ':' if depth > 0 => in_value = true,
+                    _ => {}
+                }
+                if c != ':' {
+                    in_value = false;
+                }
+                self.push(&rest[..1], Style::default());
+                1
+            } else {
+                // Values are split into words, so any numbers in them can be highlighted
+                let len = rest
+                    .find(|c: char| {
+                        matches!(c, '{' | '}' | ':' | ';' | '"' | '\'' | '/' | '@')
+                            || (in_value && c.is_whitespace())
+                    })
+                    .unwrap_or(rest.len())
+                    .max(c.len_utf8());
+                let style = if depth == 0 {
+                    tag()
+                } else if in_value {
+                    Style::default()
+                } else {
+                    attribute()
+                };
+                self.push(&rest[..len], style);
+                len
+            };
+
+            rest = &rest[len..];
+        }
+    }
+
+    fn javascript(&mut self, source: &str) {
+        let mut rest = source;
+
+        while let Some(c) = rest.chars().next() {
+            let len = if rest.starts_with("//") {
+                let len = rest.find('\n').unwrap_or(rest.len());
+                self.push(&rest[..len], comment());
+                len
+            } else if rest.starts_with("/*") {
+                let len = find_end(rest, "*/");
+                self.push(&rest[..len], comment());
+                len
+            } else if c == '"' || c == '\'' || c == '`' {
+                let len = string_length(rest, c);
+                self.push(&rest[..len], string());
+                len
+            } else if c.is_ascii_digit() {
+                let len = word_length(rest);
+                self.push(&rest[..len], literal());
+                len
+            } else if c.is_alphabetic() || c == '_' || c == '$' {
+                let len = word_length(rest);
+                let word = &rest[..len];
+                let style = if JS_KEYWORDS.contains(&word) {
+                    keyword()
+                } else {
+                    Style::default()
+                };
+                self.push(word, style);
+                len
+            } else {
+                self.push(&rest[..c.len_utf8()], Style::default());
+                c.len_utf8()
+            };
+
+            rest = &rest[len..];
+        }
+    }
+
+    fn language(&mut self, source: &str, language: Language) {
+        match language {
+            Language::Html => self.html(source),
+            Language::Css => self.css(source),
+            Language::JavaScript => self.javascript(source),
+            Language::Plain => self.push(source, Style::default()),
+        }
+    }
+}
+
+fn is_tag_start(source: &str) -> bool {
+    let mut chars = source.chars();
+    chars.next() == Some('<')
+        && chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/')
+}
+
+/// Returns the length of the source up to and including the given terminator, or the whole
+/// source when it is never terminated
+fn find_end(source: &str, terminator: &str) -> usize {
+    source[1..]
+        .find(terminator)
+        .map(|idx| idx + 1 + terminator.len())
+        .unwrap_or(source.len())
+}
+
+/// Returns the length of the tag at the start of the source, skipping any '>' in quoted
+/// attribute values
+fn tag_length(source: &str) -> usize {
+    let mut quote = None;
+    for (idx, c) in source.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return idx + 1,
+            _ => {}
+        }
+    }
+
+    source.len()
+}
+
+fn entity_length(source: &str) -> usize {
+    let name_len = source[1..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+        .unwrap_or(source.len() - 1);
+
+    if name_len > 0 && source[1 + name_len..].starts_with(';') {
+        name_len + 2
+    } else {
+        1
+    }
+}
+
+/// Returns the length of the string literal at the start of the source, including its quotes
+fn string_length(source: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (idx, c) in source.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            // Only template literals can span multiple lines
+            '\n' if quote != '`' => return idx,
+            c if c == quote => return idx + 1,
+            _ => {}
+        }
+    }
+
+    source.len()
+}
+
+fn word_length(source: &str) -> usize {
+    source
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$' || c == '-' || c == '.'))
+        .unwrap_or(source.len())
+}
+
+/// Lays out the given source with line numbers and syntax highlighting. Long lines are wrapped,
+/// with the continued part left unnumbered.
+pub fn render_source(source: &str, language: Language, width: usize) -> RenderedPage {
+    let source = source.replace("\r\n", "\n").replace('\t', "    ");
+
+    let mut highlighter = Highlighter::default();
+    highlighter.language(&source, language);
+
+    // Split the highlighted pieces into source lines
+    let mut source_lines: Vec<Vec<(String, Style)>> = vec![vec![]];
+    for (text, style) in highlighter.spans {
+        for (idx, part) in text.split('\n').enumerate() {
+            if idx > 0 {
+                source_lines.push(vec![]);
+            }
+            if !part.is_empty() {
+                source_lines
+                    .last_mut()
+                    .expect("line")
+                    .push((part.into(), style));
+            }
+        }
+    }
+    if source.ends_with('\n') {
+        source_lines.pop();
+    }
+
+    let number_width = source_lines.len().to_string().len();
+    let text_width = width.saturating_sub(number_width + 3).max(1);
+
+    let mut lines = vec![];
+    for (idx, pieces) in source_lines.into_iter().enumerate() {
+        for (row_idx, row) in wrap(pieces, text_width).into_iter().enumerate() {
+            let number = if row_idx == 0 {
+                (idx + 1).to_string()
+            } else {
+                String::new()
+            };
+
+            let mut spans = vec![Span::styled(
+                format!("{:>width$} │ ", number, width = number_width),
+                gutter(),
+            )];
+            spans.extend(row);
+            lines.push(Line::from(spans));
+        }
+    }
+
+    RenderedPage {
+        width,
+        lines,
+        links: vec![],
+        base: None,
+    }
+}
+
+/// Breaks a line of highlighted pieces into rows that fit the given width
+fn wrap(pieces: Vec<(String, Style)>, width: usize) -> Vec<Vec<Span<'static>>> {
+    let mut rows = vec![vec![]];
+    let mut col = 0;
+
+    for (text, style) in pieces {
+        let mut current = String::new();
+        for c in text.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if col > 0 && col + char_width > width {
+                if !current.is_empty() {
+                    rows.last_mut()
+                        .expect("row")
+                        .push(Span::styled(std::mem::take(&mut current), style));
+                }
+                rows.push(vec![]);
+                col = 0;
+            }
+            current.push(c);
+            col += char_width;
+        }
+        if !current.is_empty() {
+            rows.last_mut()
+                .expect("row")
+                .push(Span::styled(current, style));
+        }
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(page: &RenderedPage) -> Vec<String> {
+        page.lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn highlights_text_starting_with_multibyte_characters() {
+        let page = render_source("<p>één</p>\n<b>日本</b>", Language::Html, 80);
+
+        let lines = text(&page);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("<p>één</p>"));
+        assert!(lines[1].ends_with("<b>日本</b>"));
+    }
+}
@@ -0,0 +1,550 @@
+use crate::dive::render::dom::{Element, Node};
+use ratatui::prelude::*;
+use unicode_width::UnicodeWidthStr;
+
+/// Words longer than this will not be taken into account for the minimum column width, but
+/// will be broken up instead. This keeps long urls from pushing tables into the linear layout.
+const MAX_MIN_WIDTH: usize = 24;
+
+/// Maximum number of columns or rows a single cell can span
+const MAX_SPAN: usize = 100;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+struct Cell<'a> {
+    /// Element of the cell, or None for positions in the grid that are not covered by any cell
+    element: Option<&'a Element>,
+    row: usize,
+    col: usize,
+    colspan: usize,
+    rowspan: usize,
+    align: Align,
+    lines: Vec<Line<'static>>,
+    /// Width of the widest line, when laid out in the full width of the table
+    max_width: usize,
+    /// Hrefs of the links in the cell, and the number of links in the cells before it
+    links: Vec<String>,
+    first_link: usize,
+}
+
+/// Lays out a table as a grid with box drawing borders. When the table does not fit in the
+/// given width, the cells are laid out below each other instead. Returns the lines and the
+/// hrefs of all links in the table.
+///
+/// The cell callback lays out the content of a single cell for the given width, numbering its
+/// links after the given number of links in the table, and returns the lines and the hrefs of
+/// those links. Every cell is laid out once in the full width to measure it. Those lines are
+/// reused when the cell gets at least the width it needs, so nested tables are not laid out
+/// over and over.
+pub fn layout<F>(
+    table: &Element,
+    width: usize,
+    mut cell_layout: F,
+) -> (Vec<Line<'static>>, Vec<String>)
+where
+    F: FnMut(&Element, usize, usize) -> (Vec<Line<'static>>, Vec<String>),
+{
+    let rows = collect_rows(table);
+    let (mut cells, grid) = build_grid(&rows);
+    if cells.is_empty() {
+        return (vec![], vec![]);
+    }
+
+    // Measure in the order of the cells, so links are numbered the way they are shown
+    let mut next_link = 0;
+    for cell in cells.iter_mut() {
+        if let Some(element) = cell.element {
+            let (lines, links) = cell_layout(element, width, next_link);
+            cell.max_width = lines.iter().map(|line| line.width()).max().unwrap_or(0);
+            cell.lines = lines;
+            cell.first_link = next_link;
+            next_link += links.len();
+            cell.links = links;
+        }
+    }
+    let links = cells.iter().flat_map(|cell| cell.links.clone()).collect();
+
+    let cols = grid[0].len();
+    // Every column has a border on the left and a space of padding on both sides
+    let overhead = 3 * cols + 1;
+
+    let mut min = vec![1; cols];
+    let mut max = vec![1; cols];
+
+    // Single column cells first, so spanning cells only need to add what is missing
+    let mut order: Vec<usize> = (0..cells.len()).collect();
+    order.sort_by_key(|idx| cells[*idx].colspan);
+    for idx in order {
+        let cell = &cells[idx];
+        let Some(element) = cell.element else {
+            continue;
+        };
+
+        let cell_min = min_width(element);
+        let cell_max = cell.max_width;
+
+        let range = cell.col..cell.col + cell.colspan;
+        let spacing = 3 * (cell.colspan - 1);
+        grow(&mut min[range.clone()], cell_min.saturating_sub(spacing));
+        grow(&mut max[range], cell_max.saturating_sub(spacing));
+    }
+
+    for col in 0..cols {
+        max[col] = max[col].max(min[col]);
+    }
+
+    let min_total = min.iter().sum::<usize>() + overhead;
+    if min_total > width {
+        return (linear(cells), links);
+    }
+
+    let widths = column_widths(&min, &max, width - overhead);
+
+    for cell in cells.iter_mut() {
+        if let Some(element) = cell.element {
+            let w = span_width(&widths, cell.col, cell.colspan);
+            if w < cell.max_width {
+                cell.lines = cell_layout(element, w, cell.first_link).0;
+            }
+        }
+    }
+
+    // Row heights are decided by the cells that don't span rows. Cells that span multiple rows
+    // will enlarge their last row when they don't fit.
+    let mut heights = vec![1; grid.len()];
+    for cell in cells.iter().filter(|cell| cell.rowspan == 1) {
+        heights[cell.row] = heights[cell.row].max(cell.lines.len());
+    }
+    for cell in cells.iter().filter(|cell| cell.rowspan > 1) {
+        let available: usize = heights[cell.row..cell.row + cell.rowspan].iter().sum();
+        if cell.lines.len() > available {
+            heights[cell.row + cell.rowspan - 1] += cell.lines.len() - available;
+        }
+    }
+
+    let mut lines = vec![];
+    lines.push(border_line(&grid, &widths, None, Some(0)));
+    for row in 0..grid.len() {
+        for line_idx in 0..heights[row] {
+            lines.push(content_line(
+                &grid, &cells, &widths, &heights, row, line_idx,
+            ));
+        }
+
+        let below = if row + 1 < grid.len() {
+            Some(row + 1)
+        } else {
+            None
+        };
+        lines.push(border_line(&grid, &widths, Some(row), below));
+    }
+
+    (lines, links)
+}
+
+/// Returns the rows of a table, with the cells found in each row
+fn collect_rows(table: &Element) -> Vec<Vec<&Element>> {
+    let mut rows = vec![];
+
+    for child in &table.children {
+        let Node::Element(el) = child else {
+            continue;
+        };
+
+        match el.name.as_str() {
+            "tr" => rows.push(collect_cells(el)),
+            "thead" | "tbody" | "tfoot" => {
+                for child in &el.children {
+                    if let Node::Element(tr) = child {
+                        if tr.name == "tr" {
+                            rows.push(collect_cells(tr));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    rows
+}
+
+fn collect_cells(tr: &Element) -> Vec<&Element> {
+    tr.children
+        .iter()
+        .filter_map(|child| match child {
+            Node::Element(el) if el.name == "td" || el.name == "th" => Some(el),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Places all the cells in a grid, taking colspan and rowspan into account. Every position in
+/// the grid holds the index of the cell that covers it. Positions that are not covered by any
+/// cell get an empty cell of their own.
+fn build_grid<'a>(rows: &[Vec<&'a Element>]) -> (Vec<Cell<'a>>, Vec<Vec<usize>>) {
+    let mut cells: Vec<Cell> = vec![];
+    let mut grid: Vec<Vec<Option<usize>>> = vec![vec![]; rows.len()];
+
+    for (row, row_cells) in rows.iter().enumerate() {
+        let mut col = 0;
+        for element in row_cells {
+            while grid[row].get(col).is_some_and(|c| c.is_some()) {
+                col += 1;
+            }
+
+            let rowspan = span(element, "rowspan").min(rows.len() - row);
+
+            // Cells spanning rows from above stay where they are, so a colspan that would
+            // reach into one of them is cut short
+            let free = |col: usize| {
+                grid.iter()
+                    .skip(row)
+                    .take(rowspan)
+                    .all(|grid_row| grid_row.get(col).is_none_or(|c| c.is_none()))
+            };
+            let colspan = span(element, "colspan");
+            let colspan = (1..colspan)
+                .find(|offset| !free(col + offset))
+                .unwrap_or(colspan);
+
+            let idx = cells.len();
+            for grid_row in grid.iter_mut().skip(row).take(rowspan) {
+                if grid_row.len() < col + colspan {
+                    grid_row.resize(col + colspan, None);
+                }
+                grid_row[col..col + colspan].fill(Some(idx));
+            }
+
+            cells.push(Cell {
+                element: Some(element),
+                row,
+                col,
+                colspan,
+                rowspan,
+                align: alignment(element),
+                lines: vec![],
+                max_width: 0,
+                links: vec![],
+                first_link: 0,
+            });
+            col += colspan;
+        }
+    }
+
+    let cols = grid.iter().map(|r| r.len()).max().unwrap_or(0);
+    if cols == 0 {
+        return (vec![], vec![]);
+    }
+
+    let mut filled = vec![];
+    for (row, r) in grid.into_iter().enumerate() {
+        let mut filled_row = vec![];
+        for col in 0..cols {
+            match r.get(col).copied().flatten() {
+                Some(idx) => filled_row.push(idx),
+                None => {
+                    filled_row.push(cells.len());
+                    cells.push(Cell {
+                        element: None,
+                        row,
+                        col,
+                        colspan: 1,
+                        rowspan: 1,
+                        align: Align::Left,
+                        lines: vec![],
+                        max_width: 0,
+                        links: vec![],
+                        first_link: 0,
+                    });
+                }
+            }
+        }
+        filled.push(filled_row);
+    }
+
+    (cells, filled)
+}
+
+fn span(el: &Element, attr: &str) -> usize {
+    el.attr(attr)
+        .and_then(|s| s.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, MAX_SPAN)
+}
+
+fn alignment(el: &Element) -> Align {
+    match el.attr("align").map(|a| a.to_lowercase()).as_deref() {
+        Some("center") => Align::Center,
+        Some("right") => Align::Right,
+        Some(_) => Align::Left,
+        None if el.name == "th" => Align::Center,
+        None => Align::Left,
+    }
+}
+
+/// Width of the longest word in the element, which is the smallest width it can be laid out in
+/// without breaking words.
+fn min_width(el: &Element) -> usize {
+    el.text()
+        .split(|c: char| c.is_ascii_whitespace())
+        .map(|word| word.width())
+        .max()
+        .unwrap_or(0)
+        .clamp(1, MAX_MIN_WIDTH)
+}
+
+/// Grows the given columns so that together they are at least the given width
+fn grow(cols: &mut [usize], width: usize) {
+    let total: usize = cols.iter().sum();
+    if total >= width {
+        return;
+    }
+
+    let missing = width - total;
+    let count = cols.len();
+    for (idx, col) in cols.iter_mut().enumerate() {
+        *col += missing / count + if idx < missing % count { 1 } else { 0 };
+    }
+}
+
+/// Calculates the width of every column. Columns get their maximum width when everything
+/// fits, otherwise the available space above the minimum widths is shared in proportion to
+/// how much extra space each column would like to have.
+fn column_widths(min: &[usize], max: &[usize], available: usize) -> Vec<usize> {
+    let max_total: usize = max.iter().sum();
+    if max_total <= available {
+        return max.to_vec();
+    }
+
+    let min_total: usize = min.iter().sum();
+    let extra = available - min_total;
+    let wanted = max_total - min_total;
+
+    let mut widths: Vec<usize> = min
+        .iter()
+        .zip(max)
+        .map(|(min, max)| min + (max - min) * extra / wanted)
+        .collect();
+
+    // Hand out what is left because of rounding
+    let mut left = available - widths.iter().sum::<usize>();
+    for (idx, width) in widths.iter_mut().enumerate() {
+        if left == 0 {
+            break;
+        }
+        if *width < max[idx] {
+            *width += 1;
+            left -= 1;
+        }
+    }
+
+    widths
+}
+
+fn span_width(widths: &[usize], col: usize, colspan: usize) -> usize {
+    widths[col..col + colspan].iter().sum::<usize>() + 3 * (colspan - 1)
+}
+
+/// Draws a horizontal border line between the given rows (None being outside of the table)
+fn border_line(
+    grid: &[Vec<usize>],
+    widths: &[usize],
+    above: Option<usize>,
+    below: Option<usize>,
+) -> Line<'static> {
+    let cols = widths.len();
+    let cell = |row: Option<usize>, col: usize| -> Option<usize> {
+        match (row, col < cols) {
+            (Some(row), true) => Some(grid[row][col]),
+            _ => None,
+        }
+    };
+
+    // A horizontal line is drawn above a column, unless a cell spans over it
+    let horizontal = |col: usize| -> bool {
+        match (cell(above, col), cell(below, col)) {
+            (Some(a), Some(b)) => a != b,
+            _ => true,
+        }
+    };
+    // A vertical line is drawn between two columns, unless a cell spans over it
+    let vertical = |row: Option<usize>, col: usize| -> bool {
+        match row {
+            None => false,
+            Some(_) => col == 0 || col == cols || cell(row, col - 1) != cell(row, col),
+        }
+    };
+
+    let mut s = String::new();
+    for col in 0..=cols {
+        let left = col > 0 && horizontal(col - 1);
+        let right = col < cols && horizontal(col);
+        s.push(junction(
+            vertical(above, col),
+            vertical(below, col),
+            left,
+            right,
+        ));
+
+        if let Some(width) = widths.get(col) {
+            let fill = if horizontal(col) { "─" } else { " " };
+            s.push_str(&fill.repeat(width + 2));
+        }
+    }
+
+    Line::styled(s, Style::default().fg(Color::Gray))
+}
+
+fn junction(up: bool, down: bool, left: bool, right: bool) -> char {
+    match (up, down, left, right) {
+        (true, true, true, true) => '┼',
+        (true, true, true, false) => '┤',
+        (true, true, false, true) => '├',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, false) | (true, false, false, false) | (false, true, false, false) => {
+            '│'
+        }
+        (false, false, true, _) | (false, false, _, true) => '─',
+        (false, false, false, false) => ' ',
+    }
+}
+
+/// Draws a single line of content of the given row
+fn content_line(
+    grid: &[Vec<usize>],
+    cells: &[Cell],
+    widths: &[usize],
+    heights: &[usize],
+    row: usize,
+    line_idx: usize,
+) -> Line<'static> {
+    let border = Style::default().fg(Color::Gray);
+    let mut spans = vec![];
+
+    let mut col = 0;
+    while col < widths.len() {
+        spans.push(Span::styled("│", border));
+
+        let cell = &cells[grid[row][col]];
+        let width = span_width(widths, cell.col, cell.colspan);
+        let offset: usize = heights[cell.row..row].iter().sum::<usize>() + line_idx;
+
+        spans.push(Span::raw(" "));
+        match cell.lines.get(offset) {
+            Some(line) => {
+                let padding = width.saturating_sub(line.width());
+                let (before, after) = match cell.align {
+                    Align::Left => (0, padding),
+                    Align::Center => (padding / 2, padding - padding / 2),
+                    Align::Right => (padding, 0),
+                };
+                if before > 0 {
+                    spans.push(Span::raw(" ".repeat(before)));
+                }
+                spans.extend(line.spans.iter().cloned());
+                spans.push(Span::raw(" ".repeat(after)));
+            }
+            None => spans.push(Span::raw(" ".repeat(width))),
+        }
+        spans.push(Span::raw(" "));
+
+        col += cell.colspan;
+    }
+    spans.push(Span::styled("│", border));
+
+    Line::from(spans)
+}
+
+/// Lays out the table without any grid: every cell on its own lines, with an empty line
+/// between rows. The cells are shown as measured, in the full width of the table.
+fn linear(cells: Vec<Cell>) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    let mut last_row = None;
+
+    for cell in cells.into_iter().filter(|cell| cell.element.is_some()) {
+        if last_row.is_some_and(|row| row != cell.row) {
+            lines.push(Line::default());
+        }
+        last_row = Some(cell.row);
+        lines.extend(cell.lines);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dive::render::dom::parse;
+
+    fn table(source: &str) -> Element {
+        parse(source).find("table").expect("table").clone()
+    }
+
+    /// Lays out every cell as its plain text
+    fn plain_layout(
+        cell: &Element,
+        _width: usize,
+        _first_link: usize,
+    ) -> (Vec<Line<'static>>, Vec<String>) {
+        (vec![Line::raw(cell.text())], vec![])
+    }
+
+    #[test]
+    fn colspans_do_not_overlap_rowspans() {
+        let table = table(
+            "<table><tr><td>A</td><td rowspan=2>B</td></tr>\
+             <tr><td colspan=2>C</td><td>D</td></tr></table>",
+        );
+        let rows = collect_rows(&table);
+        let (cells, grid) = build_grid(&rows);
+
+        // C is cut short so B keeps its place, and D ends up next to B
+        assert_eq!(grid, vec![vec![0, 1, 4], vec![2, 1, 3]]);
+        assert_eq!(cells[2].colspan, 1);
+        assert_eq!(cells[3].col, 2);
+    }
+
+    #[test]
+    fn cells_that_fit_are_laid_out_once() {
+        let table = table("<table><tr><td>one</td><td>two</td></tr></table>");
+        let mut calls = 0;
+        let (lines, _) = layout(&table, 80, |cell, width, first_link| {
+            calls += 1;
+            plain_layout(cell, width, first_link)
+        });
+
+        assert_eq!(calls, 2);
+        let text = lines[1]
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect::<String>();
+        assert_eq!(text, "│ one │ two │");
+    }
+
+    #[test]
+    fn numbers_links_in_the_order_of_the_cells() {
+        let source = "<p><a href=/before>before</a></p>\
+            <table><tr><td><a href=/a>a</a></td><td>\
+            <table><tr><td><a href=/b>b</a></td></tr></table></td></tr>\
+            <tr><td colspan=2><a href=/c>c</a> <a href=/d>d</a></td></tr></table>";
+
+        // Wide enough for the grid, and narrow enough to lay out linearly
+        for width in [80, 12] {
+            let page = crate::dive::render::render_html(source, width);
+            assert_eq!(page.links, vec!["/before", "/a", "/b", "/c", "/d"]);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use ratatui::prelude::*;
+use regex::{Regex, RegexBuilder};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    /// Treat the query as a regular expression instead of plain text
+    pub regex: bool,
+}
+
+/// A single match of the query. Start and end are byte offsets into the text of the line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Text search through the rendered lines of a page
+pub struct PageSearch {
+    pub query: String,
+    pub options: SearchOptions,
+    pub matches: Vec<SearchMatch>,
+    /// Index of the match that is currently selected
+    pub current: Option<usize>,
+    /// Set when the query is not a valid regular expression
+    pub error: Option<String>,
+    regex: Option<Regex>,
+    /// Width of the page the matches have been found for
+    width: Option<usize>,
+}
+
+impl PageSearch {
+    pub fn new(query: &str, options: SearchOptions) -> Self {
+        let pattern = if options.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+
+        let (regex, error) = if query.is_empty() {
+            (None, None)
+        } else {
+            match RegexBuilder::new(&pattern)
+                .case_insensitive(!options.case_sensitive)
+                .build()
+            {
+                Ok(regex) => (Some(regex), None),
+                Err(e) => (None, Some(e.to_string())),
+            }
+        };
+
+        Self {
+            query: query.into(),
+            options,
+            matches: vec![],
+            current: None,
+            error,
+            regex,
+            width: None,
+        }
+    }
+
+    /// Finds all matches in the given lines when they have not been found for this width yet.
+    /// The first match at or below the scroll position is selected. Returns the line of the
+    /// newly selected match.
+    pub fn update(&mut self, lines: &[Line], width: usize, scroll: usize) -> Option<usize> {
+        if self.width == Some(width) {
+            return None;
+        }
+        self.width = Some(width);
+
+        self.matches.clear();
+        let regex = self.regex.as_ref()?;
+
+        for (idx, line) in lines.iter().enumerate() {
+            let text = line_text(line);
+            for m in regex.find_iter(&text) {
+                // Empty matches can't be highlighted
+                if m.start() == m.end() {
+                    continue;
+                }
+                self.matches.push(SearchMatch {
+                    line: idx,
+                    start: m.start(),
+                    end: m.end(),
+                });
+            }
+        }
+
+        if self.matches.is_empty() {
+            self.current = None;
+            return None;
+        }
+
+        let first = self
+            .matches
+            .iter()
+            .position(|m| m.line >= scroll)
+            .unwrap_or(0);
+        self.current = Some(first);
+
+        Some(self.matches[first].line)
+    }
+
+    /// Selects the next match, wrapping around at the end of the page. Returns its line.
+    pub fn next(&mut self) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        let next = match self.current {
+            Some(current) => (current + 1) % self.matches.len(),
+            None => 0,
+        };
+        self.current = Some(next);
+
+        Some(self.matches[next].line)
+    }
+
+    /// Selects the previous match, wrapping around at the start of the page. Returns its line.
+    pub fn prev(&mut self) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        let prev = match self.current {
+            Some(0) | None => self.matches.len() - 1,
+            Some(current) => current - 1,
+        };
+        self.current = Some(prev);
+
+        Some(self.matches[prev].line)
+    }
+
+    /// Returns a short description of the search state, like "3/17"
+    pub fn status(&self) -> String {
+        if let Some(error) = &self.error {
+            return format!("invalid regex: {}", error.lines().last().unwrap_or(""));
+        }
+
+        match self.current {
+            Some(current) => format!("{}/{}", current + 1, self.matches.len()),
+            None => "0/0".into(),
+        }
+    }
+
+    /// Returns the given line with all the matches found on it highlighted
+    pub fn highlight(&self, line_idx: usize, line: Line<'static>) -> Line<'static> {
+        let first = self.matches.partition_point(|m| m.line < line_idx);
+        let matches = self.matches[first..]
+            .iter()
+            .enumerate()
+            .take_while(|(_, m)| m.line == line_idx)
+            .map(|(idx, m)| (first + idx, m))
+            .collect::<Vec<_>>();
+
+        if matches.is_empty() {
+            return line;
+        }
+
+        let mut spans = vec![];
+        let mut offset = 0;
+        for span in line.spans {
+            let content = span.content.as_ref();
+            let span_end = offset + content.len();
+
+            let mut pos = offset;
+            for (idx, m) in matches.iter() {
+                if m.end <= pos || m.start >= span_end {
+                    continue;
+                }
+
+                let start = m.start.max(pos);
+                let end = m.end.min(span_end);
+                if start > pos {
+                    spans.push(Span::styled(
+                        content[pos - offset..start - offset].to_string(),
+                        span.style,
+                    ));
+                }
+
+                let style = if Some(*idx) == self.current {
+                    Style::default().fg(Color::Black).bg(Color::LightRed)
+                } else {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                };
+                spans.push(Span::styled(
+                    content[start - offset..end - offset].to_string(),
+                    span.style.patch(style),
+                ));
+                pos = end;
+            }
+
+            if pos < span_end {
+                spans.push(Span::styled(
+                    content[pos - offset..].to_string(),
+                    span.style,
+                ));
+            }
+            offset = span_end;
+        }
+
+        Line::from(spans)
+    }
+}
+
+fn line_text(line: &Line) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(texts: &[&str]) -> Vec<Line<'static>> {
+        texts
+            .iter()
+            .map(|text| Line::raw(text.to_string()))
+            .collect()
+    }
+
+    fn search(query: &str, options: SearchOptions, texts: &[&str]) -> PageSearch {
+        let mut search = PageSearch::new(query, options);
+        search.update(&lines(texts), 80, 0);
+        search
+    }
+
+    fn texts(line: &Line) -> Vec<String> {
+        line.spans
+            .iter()
+            .map(|span| span.content.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn matches_case_insensitive_unless_asked() {
+        let texts = ["Rust is rusty", "RUST"];
+        let found = search("rust", SearchOptions::default(), &texts);
+        assert_eq!(found.matches.len(), 3);
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..SearchOptions::default()
+        };
+        let found = search("rust", options, &texts);
+        assert_eq!(
+            found.matches,
+            vec![SearchMatch {
+                line: 0,
+                start: 8,
+                end: 12
+            }]
+        );
+    }
+
+    #[test]
+    fn treats_the_query_as_text_unless_it_is_a_regex() {
+        let texts = ["a.b axb", "1+1 11"];
+        let found = search("a.b", SearchOptions::default(), &texts);
+        assert_eq!(found.matches.len(), 1);
+        assert_eq!(
+            search("1+1", SearchOptions::default(), &texts)
+                .matches
+                .len(),
+            1
+        );
+
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(search("a.b", options, &texts).matches.len(), 2);
+        assert_eq!(search(r"\d+", options, &texts).matches.len(), 3);
+    }
+
+    #[test]
+    fn reports_empty_and_invalid_queries() {
+        let found = search("", SearchOptions::default(), &["some text"]);
+        assert!(found.matches.is_empty());
+        assert_eq!(found.error, None);
+        assert_eq!(found.status(), "0/0");
+
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        let found = search("(unclosed", options, &["(unclosed"]);
+        assert!(found.matches.is_empty());
+        assert!(found.error.is_some());
+        assert!(found.status().starts_with("invalid regex: "));
+
+        // Without the regex option the same query is plain text
+        let found = search("(unclosed", SearchOptions::default(), &["(unclosed"]);
+        assert_eq!(found.status(), "1/1");
+    }
+
+    #[test]
+    fn selects_the_first_match_at_or_below_the_scroll_position() {
+        let texts = ["match", "", "match", "match", ""];
+        let mut found = PageSearch::new("match", SearchOptions::default());
+        assert_eq!(found.update(&lines(&texts), 80, 1), Some(2));
+        assert_eq!(found.current, Some(1));
+        assert_eq!(found.status(), "2/3");
+
+        // Nothing changes until the page is laid out for another width
+        assert_eq!(found.update(&lines(&texts), 80, 0), None);
+        assert_eq!(found.current, Some(1));
+
+        // Past the last match, the search starts at the top of the page
+        assert_eq!(found.update(&lines(&texts), 40, 4), Some(0));
+        assert_eq!(found.status(), "1/3");
+    }
+
+    #[test]
+    fn wraps_around_in_both_directions() {
+        let mut found = search("x", SearchOptions::default(), &["x", "x x"]);
+        assert_eq!(found.status(), "1/3");
+
+        assert_eq!(found.prev(), Some(1));
+        assert_eq!(found.status(), "3/3");
+        assert_eq!(found.next(), Some(0));
+        assert_eq!(found.status(), "1/3");
+        assert_eq!(found.next(), Some(1));
+        assert_eq!(found.next(), Some(1));
+        assert_eq!(found.next(), Some(0));
+
+        let mut none = search("y", SearchOptions::default(), &["x"]);
+        assert_eq!(none.next(), None);
+        assert_eq!(none.prev(), None);
+        assert_eq!(none.status(), "0/0");
+    }
+
+    #[test]
+    fn highlights_matches_across_spans() {
+        let line = Line::from(vec![
+            Span::raw("hello wo"),
+            Span::styled("rld again", Style::default().add_modifier(Modifier::BOLD)),
+        ]);
+        let mut found = PageSearch::new("world", SearchOptions::default());
+        found.update(std::slice::from_ref(&line), 80, 0);
+
+        let highlighted = found.highlight(0, line);
+        assert_eq!(texts(&highlighted), vec!["hello ", "wo", "rld", " again"]);
+        assert_eq!(highlighted.spans[1].style.bg, Some(Color::LightRed));
+        assert_eq!(highlighted.spans[2].style.bg, Some(Color::LightRed));
+        assert!(highlighted.spans[2]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert_eq!(highlighted.spans[3].style.bg, None);
+    }
+
+    #[test]
+    fn highlights_matches_in_multibyte_text() {
+        let line = Line::from(vec![Span::raw("één "), Span::raw("naïve één")]);
+        let mut found = PageSearch::new("ÉÉN", SearchOptions::default());
+        found.update(std::slice::from_ref(&line), 80, 0);
+        assert_eq!(found.matches.len(), 2);
+
+        // Only the selected match gets the current highlight
+        let highlighted = found.highlight(0, line);
+        assert_eq!(texts(&highlighted), vec!["één", " ", "naïve ", "één"]);
+        assert_eq!(highlighted.spans[0].style.bg, Some(Color::LightRed));
+        assert_eq!(highlighted.spans[3].style.bg, Some(Color::Yellow));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::dive::tab_manager::HistoryEntry;
+use crate::dive::workspace_manager::DEFAULT_WORKSPACE;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// State of a single tab that is kept across restarts
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TabSession {
+    pub name: String,
+    /// Session history of the tab. The current page is found at history_idx.
+    pub history: Vec<HistoryEntry>,
+    pub history_idx: usize,
+    /// Vertical scroll offset of the current page
+    pub scroll: usize,
+}
+
+/// The tabs of a single workspace
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceSession {
+    pub name: String,
+    pub tabs: Vec<TabSession>,
+    /// Index of the tab that was current
+    pub current: usize,
+}
+
+/// The workspaces and their open tabs, as saved on quit and autosaved while running
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub workspaces: Vec<WorkspaceSession>,
+    /// Index of the workspace that was active
+    #[serde(default)]
+    pub active: usize,
+    /// Set when the session has been saved on quit. An autosaved session without it means dive
+    /// did not get the chance to quit properly.
+    #[serde(default)]
+    pub clean_exit: bool,
+    /// Unix time the session has been saved
+    #[serde(default)]
+    pub saved: i64,
+}
+
+/// Session as found on disk. Sessions saved before there were workspaces only have the tabs
+/// and the current tab.
+#[derive(Deserialize)]
+struct SavedSession {
+    #[serde(flatten)]
+    session: Session,
+    #[serde(default)]
+    tabs: Vec<TabSession>,
+    #[serde(default)]
+    current: usize,
+}
+
+impl From<SavedSession> for Session {
+    fn from(saved: SavedSession) -> Self {
+        let mut session = saved.session;
+        if session.workspaces.is_empty() && !saved.tabs.is_empty() {
+            session.workspaces.push(WorkspaceSession {
+                name: DEFAULT_WORKSPACE.into(),
+                tabs: saved.tabs,
+                current: saved.current,
+            });
+            session.active = 0;
+        }
+
+        session
+    }
+}
+
+impl Session {
+    /// Returns the default location of the session file, inside the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join("dive").join("session.json"))
+    }
+
+    /// Reads the session from the given file. Returns None when there is no (valid) session.
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<SavedSession>(&content) {
+            Ok(session) => Some(session.into()),
+            Err(e) => {
+                log::warn!("Unable to read session from {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Writes the session to the given file. The session is written to a temporary file first,
+    /// so a crash halfway through leaves the previous session intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(self)?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn saves_and_loads_sessions() {
+        let path = std::env::temp_dir()
+            .join(format!("dive-session-{}", Uuid::new_v4()))
+            .join("session.json");
+        assert_eq!(Session::load(&path), None);
+
+        let session = Session {
+            workspaces: vec![
+                WorkspaceSession {
+                    name: "docs".into(),
+                    tabs: vec![TabSession {
+                        name: "Example".into(),
+                        history: vec![
+                            HistoryEntry {
+                                url: "https://example.com/".into(),
+                                scroll: 12,
+                            },
+                            HistoryEntry {
+                                url: "https://example.com/about".into(),
+                                scroll: 0,
+                            },
+                        ],
+                        history_idx: 1,
+                        scroll: 3,
+                    }],
+                    current: 0,
+                },
+                WorkspaceSession {
+                    name: "oncall".into(),
+                    tabs: vec![],
+                    current: 0,
+                },
+            ],
+            active: 1,
+            clean_exit: true,
+            saved: 1_700_000_000,
+        };
+        session.save(&path).unwrap();
+
+        assert_eq!(Session::load(&path), Some(session));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn loads_sessions_saved_before_workspaces() {
+        let path = std::env::temp_dir()
+            .join(format!("dive-session-{}", Uuid::new_v4()))
+            .join("session.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let json = r#"{
+            "tabs": [
+                {"name": "Docs", "history": [{"url": "https://docs.rs/", "scroll": 0}], "history_idx": 0, "scroll": 4},
+                {"name": "Example", "history": [{"url": "https://example.com/", "scroll": 0}], "history_idx": 0, "scroll": 0}
+            ],
+            "current": 1,
+            "clean_exit": true,
+            "saved": 1700000000
+        }"#;
+        fs::write(&path, json).unwrap();
+
+        let session = Session::load(&path).unwrap();
+        assert_eq!(session.workspaces.len(), 1);
+        assert_eq!(session.active, 0);
+        assert!(session.clean_exit);
+        assert_eq!(session.saved, 1_700_000_000);
+
+        let workspace = &session.workspaces[0];
+        assert_eq!(workspace.name, DEFAULT_WORKSPACE);
+        assert_eq!(workspace.current, 1);
+        assert_eq!(workspace.tabs.len(), 2);
+        assert_eq!(workspace.tabs[0].history[0].url, "https://docs.rs/");
+        assert_eq!(workspace.tabs[0].scroll, 4);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -1,35 +1,195 @@
-use anyhow::Error;
+use crate::dive::command_queue::Command;
+use crate::dive::loader::{error_page, spawn_load, Content, LoadContext, LoadError};
+use crate::dive::render::dom;
+use crate::dive::render::registry::RendererRegistry;
+use crate::dive::render::RenderedPage;
+use crate::dive::search::PageSearch;
+use crate::dive::session::{TabSession, WorkspaceSession};
 use crate::dive::widgets::status_bar::TabInfo;
-use ureq;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::Sender;
 use url::Url;
+use uuid::Uuid;
+
+/// A single page in the session history of a tab
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub url: String,
+    /// Vertical scroll offset of the page when we navigated away from it
+    pub scroll: usize,
+}
 
 pub struct Tab {
     pub name: String,
     pub url: String,
-    pub content: String,
+    pub content: Content,
     pub secure: bool,
+    /// Id of the load that is currently in flight for this tab, if any
+    pub loading: Option<Uuid>,
+    /// Set when the last load of this tab failed
+    pub error: Option<LoadError>,
+    /// Vertical scroll offset of the current page
+    pub scroll: usize,
+    /// Session history of this tab. The current page is found at history_idx.
+    pub history: Vec<HistoryEntry>,
+    pub history_idx: usize,
+    /// Content laid out for the last width it was displayed at
+    rendered: Option<RenderedPage>,
+    /// Renderers to lay out the content with, based on its MIME type
+    renderers: Rc<RendererRegistry>,
+    /// Text search that is active on the current page
+    pub search: Option<PageSearch>,
 }
 
 impl Tab {
     pub fn info(&self) -> TabInfo {
         TabInfo {
-            name: self.name.clone(),
             url: self.url.clone(),
             secure: self.secure,
+            loading: self.is_loading(),
+        }
+    }
+
+    /// Returns the title of the page, as found in the <title> element of HTML documents
+    pub fn page_title(&self) -> Option<String> {
+        if !matches!(
+            self.content.mime_type.as_str(),
+            "text/html" | "application/xhtml+xml"
+        ) {
+            return None;
+        }
+
+        let document = dom::parse(self.content.as_text()?);
+        let title = document.find("title")?.text();
+        let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!title.is_empty()).then_some(title)
+    }
+
+    /// Replaces the content of the tab, which will be rendered again on next display
+    pub fn set_content(&mut self, content: Content) {
+        self.content = content;
+        self.rendered = None;
+        self.search = None;
+    }
+
+    /// Returns the content of the tab, laid out for the given width
+    pub fn page(&mut self, width: usize) -> &RenderedPage {
+        if self.rendered.as_ref().map(|page| page.width) != Some(width) {
+            self.rendered = Some(self.renderers.render(&self.content, width));
+        }
+
+        self.rendered.as_ref().expect("rendered page")
+    }
+
+    /// Finds the matches of the active search in the page laid out for the given width, and
+    /// scrolls to the first match when the search is new.
+    pub fn update_search(&mut self, width: usize, height: usize) {
+        self.page(width);
+
+        let (Some(search), Some(page)) = (self.search.as_mut(), self.rendered.as_ref()) else {
+            return;
+        };
+        if let Some(line) = search.update(&page.lines, width, self.scroll) {
+            self.reveal_line(line, height);
         }
     }
+
+    /// Scrolls the page so the given line is visible in a view of the given height
+    pub fn reveal_line(&mut self, line: usize, height: usize) {
+        if line < self.scroll || line >= self.scroll + height {
+            self.scroll = line.saturating_sub(height / 3);
+        }
+    }
+
+    /// Returns the absolute url of the given link number on the current page
+    pub fn link_url(&self, number: usize) -> Option<String> {
+        self.rendered.as_ref()?.link_url(number, &self.url)
+    }
+
+    /// Saves the content of the tab into the given directory, under the file name from the
+    /// url. Existing files are never overwritten. Returns the path of the saved file.
+    pub fn save_content(&self, dir: &Path) -> io::Result<PathBuf> {
+        let name = Url::parse(&self.url)
+            .ok()
+            .and_then(|url| {
+                let segment = url.path_segments()?.next_back()?;
+                // Decoded separators and control characters must not end up in the path
+                let name = percent_decode_str(segment)
+                    .decode_utf8_lossy()
+                    .chars()
+                    .filter(|c| !c.is_control() && *c != '/' && *c != '\\')
+                    .collect::<String>();
+                let name = name.trim();
+                (!name.is_empty() && name != "." && name != "..").then(|| name.to_string())
+            })
+            .unwrap_or_else(|| "download".into());
+
+        // Creating the file fails when it already exists, so a file that shows up while we are
+        // saving is not overwritten either
+        let mut path = dir.join(&name);
+        let mut counter = 1;
+        let mut file = loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => break file,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    path = dir.join(format!("{}.{}", name, counter));
+                    counter += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        file.write_all(self.content.as_bytes())?;
+        Ok(path)
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.history_idx > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.history_idx + 1 < self.history.len()
+    }
+
+    /// Moves to the given entry in the history, while remembering the scroll position of the
+    /// page we are leaving.
+    fn goto_history(&mut self, history_idx: usize) {
+        self.history[self.history_idx].scroll = self.scroll;
+
+        self.history_idx = history_idx;
+        self.url = self.history[history_idx].url.clone();
+        self.scroll = self.history[history_idx].scroll;
+    }
 }
 
 pub struct TabManager {
     pub tabs: Vec<Tab>,
     pub current: usize,
+    /// Sender on which the loader threads will report back their results
+    sender: Sender<Command>,
+    /// Renderers shared by all tabs
+    pub renderers: Rc<RendererRegistry>,
+    /// Settings, fetcher and cookies the loader threads work with
+    pub context: LoadContext,
 }
 
 impl TabManager {
-    pub fn new() -> Self {
+    pub fn new(sender: Sender<Command>, context: LoadContext) -> Self {
         Self {
             tabs: vec![],
             current: 0,
+            sender,
+            renderers: Rc::new(RendererRegistry::default()),
+            context,
         }
     }
 
@@ -37,27 +197,240 @@ impl TabManager {
         self.tabs.get(self.current).expect("No current tab")
     }
 
+    pub fn current_mut(&mut self) -> &mut Tab {
+        self.tabs.get_mut(self.current).expect("No current tab")
+    }
+
     pub fn rename(&mut self, idx: usize, name: &str) {
         if idx < self.tabs.len() {
             self.tabs[idx].name = name.into();
         }
     }
 
+    /// Opens a new tab for the given url. The tab is returned directly in a loading state,
+    /// while the actual content is fetched in the background.
     pub fn open(&mut self, name: &str, url: &str) -> usize {
-        let mut tab = Tab {
-            name: name.into(),
+        let history = vec![HistoryEntry {
             url: url.into(),
-            content: String::new(),
+            scroll: 0,
+        }];
+        self.open_with_history(name, history, 0, 0)
+    }
+
+    /// Opens a new tab with the given session history, and loads the page at history_idx
+    fn open_with_history(
+        &mut self,
+        name: &str,
+        history: Vec<HistoryEntry>,
+        history_idx: usize,
+        scroll: usize,
+    ) -> usize {
+        let url = history[history_idx].url.clone();
+        let tab = Tab {
+            name: name.into(),
+            url: url.clone(),
+            content: Content::plain(""),
             secure: false,
+            loading: None,
+            error: None,
+            scroll,
+            history,
+            history_idx,
+            rendered: None,
+            renderers: self.renderers.clone(),
+            search: None,
         };
 
-        tab.secure = url.starts_with("https://");
-        tab.content = load_content(url).expect("Failed to load content").clone();
-
         self.tabs.push(tab);
         log::debug!("Opening new tab: {}", url);
 
-        self.tabs.len() - 1
+        let idx = self.tabs.len() - 1;
+        self.load(idx, false);
+
+        idx
+    }
+
+    /// Returns a new tab manager without any tabs, that loads pages through the same channel
+    /// and context as this one
+    pub fn sibling(&self) -> TabManager {
+        Self {
+            tabs: vec![],
+            current: 0,
+            sender: self.sender.clone(),
+            renderers: self.renderers.clone(),
+            context: self.context.clone(),
+        }
+    }
+
+    /// Returns the state of all tabs under the given workspace name, so they can be restored
+    /// on the next start
+    pub fn session(&self, name: &str) -> WorkspaceSession {
+        let tabs = self
+            .tabs
+            .iter()
+            .map(|tab| TabSession {
+                name: tab.name.clone(),
+                history: tab.history.clone(),
+                history_idx: tab.history_idx,
+                scroll: tab.scroll,
+            })
+            .collect();
+
+        WorkspaceSession {
+            name: name.into(),
+            tabs,
+            current: self.current,
+        }
+    }
+
+    /// Opens the tabs of a saved workspace, after any tabs that are already open. Returns the
+    /// number of tabs that have been restored.
+    pub fn restore(&mut self, session: &WorkspaceSession) -> usize {
+        let first = self.tabs.len();
+        for tab in &session.tabs {
+            if tab.history.is_empty() {
+                continue;
+            }
+            let history_idx = tab.history_idx.min(tab.history.len() - 1);
+            self.open_with_history(&tab.name, tab.history.clone(), history_idx, tab.scroll);
+        }
+
+        let restored = self.tabs.len() - first;
+        if restored > 0 {
+            self.current = first + session.current.min(restored - 1);
+        }
+
+        restored
+    }
+
+    /// Navigates the given tab to a new url. Any forward history of the tab is discarded.
+    pub fn navigate(&mut self, idx: usize, url: &str) -> bool {
+        let Some(tab) = self.tabs.get_mut(idx) else {
+            return false;
+        };
+
+        tab.history[tab.history_idx].scroll = tab.scroll;
+        tab.history.truncate(tab.history_idx + 1);
+        tab.history.push(HistoryEntry {
+            url: url.into(),
+            scroll: 0,
+        });
+        tab.history_idx = tab.history.len() - 1;
+        tab.url = url.into();
+        tab.scroll = 0;
+
+        log::debug!("Navigating tab {} to {}", idx, url);
+        self.load(idx, false);
+        true
+    }
+
+    /// Navigates the given tab to the previous page in its history
+    pub fn back(&mut self, idx: usize) -> bool {
+        let Some(tab) = self.tabs.get_mut(idx) else {
+            return false;
+        };
+        if !tab.can_go_back() {
+            return false;
+        }
+
+        tab.goto_history(tab.history_idx - 1);
+        self.load(idx, false);
+        true
+    }
+
+    /// Navigates the given tab to the next page in its history
+    pub fn forward(&mut self, idx: usize) -> bool {
+        let Some(tab) = self.tabs.get_mut(idx) else {
+            return false;
+        };
+        if !tab.can_go_forward() {
+            return false;
+        }
+
+        tab.goto_history(tab.history_idx + 1);
+        self.load(idx, false);
+        true
+    }
+
+    /// Loads the current url of the given tab again. A hard reload bypasses any caches.
+    pub fn reload(&mut self, idx: usize, hard: bool) -> bool {
+        if idx >= self.tabs.len() {
+            return false;
+        }
+
+        log::debug!("Reloading tab {} (hard: {})", idx, hard);
+        self.load(idx, hard);
+        true
+    }
+
+    /// Starts loading the url of the given tab in the background
+    fn load(&mut self, idx: usize, hard: bool) {
+        let load_id = Uuid::new_v4();
+
+        let tab = &mut self.tabs[idx];
+        tab.loading = Some(load_id);
+        tab.error = None;
+        tab.secure = tab.url.starts_with("https://");
+
+        spawn_load(self.sender.clone(), load_id, &tab.url, hard, &self.context);
+    }
+
+    /// Stores the result of a finished load into the tab that requested it. Returns the index
+    /// of that tab, or None when the load was cancelled or the tab has been closed since.
+    pub fn finish_load(
+        &mut self,
+        load_id: Uuid,
+        result: Result<Content, LoadError>,
+    ) -> Option<usize> {
+        let idx = self.loading_tab(load_id)?;
+
+        let tab = &mut self.tabs[idx];
+        tab.loading = None;
+        match result {
+            Ok(content) => {
+                // Show where we ended up after redirects, so relative links resolve against it
+                if let Some(final_url) = content.response.final_url.clone() {
+                    if final_url != tab.url {
+                        log::debug!("Tab {} ended up at {}", idx, final_url);
+                        tab.history[tab.history_idx].url = final_url.clone();
+                        tab.secure = final_url.starts_with("https://");
+                        tab.url = final_url;
+                    }
+                }
+                tab.set_content(content);
+            }
+            Err(e) => {
+                log::error!("Failed to load {}: {}", tab.url, e);
+                tab.set_content(error_page(&tab.url, &e));
+                tab.error = Some(e);
+            }
+        }
+
+        Some(idx)
+    }
+
+    /// Returns the index of the tab that is waiting for the given load, if any
+    pub fn loading_tab(&self, load_id: Uuid) -> Option<usize> {
+        self.tabs
+            .iter()
+            .position(|tab| tab.loading == Some(load_id))
+    }
+
+    /// Cancels the load in flight for the given tab. The loader thread itself cannot be
+    /// interrupted, but its result will be discarded once it arrives.
+    pub fn cancel_load(&mut self, idx: usize) -> bool {
+        let Some(tab) = self.tabs.get_mut(idx) else {
+            return false;
+        };
+
+        if tab.loading.take().is_none() {
+            return false;
+        }
+
+        log::debug!("Cancelled loading of {}", tab.url);
+        let content = format!("Loading of {} has been cancelled", tab.url);
+        tab.set_content(Content::plain(content));
+        true
     }
 
     pub fn switch(&mut self, idx: usize) -> usize {
@@ -104,59 +477,250 @@ impl TabManager {
     }
 }
 
-fn load_content(url: &str) -> Result<String, anyhow::Error> {
-    let parts = match Url::parse(url) {
-        Ok(parts) => parts,
-        Err(e) => return Err(anyhow::Error::msg(format!("Invalid URL: {}", e))),
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dive::cache::HttpCache;
+    use crate::dive::cookies::CookieJar;
+    use crate::dive::fetcher::mock::MockFetcher;
+    use crate::dive::settings::Settings;
+    use std::fs;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
-    if parts.scheme() == "file" {
-        return Ok("File content is not yet supported".into());
+    fn tab_manager(fetcher: MockFetcher) -> (TabManager, Receiver<Command>) {
+        let (sender, receiver) = channel();
+        let context = LoadContext {
+            settings: Settings::default(),
+            fetcher: Arc::new(fetcher),
+            cookies: Arc::new(Mutex::new(CookieJar::in_memory())),
+            cache: Arc::new(Mutex::new(HttpCache::disabled())),
+        };
+        let tab_manager = TabManager::new(sender, context);
+
+        (tab_manager, receiver)
     }
-    if parts.scheme() == "data" {
-        return Ok("Data content is not yet supported".into());
+
+    /// Waits for the next load to finish, and hands its result to the tab manager
+    fn finish_next_load(
+        tab_manager: &mut TabManager,
+        receiver: &Receiver<Command>,
+    ) -> Option<usize> {
+        match receiver.recv_timeout(Duration::from_secs(5)) {
+            Ok(Command::ContentLoaded { load_id, result }) => {
+                tab_manager.finish_load(load_id, result)
+            }
+            other => panic!("expected a finished load, got {:?}", other),
+        }
     }
-    if parts.scheme() == "gosub" {
-        return process_gosub_protocol(parts);
+
+    #[test]
+    fn open_loads_the_tab_in_the_background() {
+        let fetcher = MockFetcher::new().page("https://example.com/", "text/html", "<h1>Hi</h1>");
+        let (mut tab_manager, receiver) = tab_manager(fetcher);
+
+        let idx = tab_manager.open("Example", "https://example.com/");
+        assert_eq!(idx, 0);
+        assert!(tab_manager.tabs[idx].is_loading());
+        assert!(tab_manager.tabs[idx].secure);
+
+        assert_eq!(finish_next_load(&mut tab_manager, &receiver), Some(idx));
+        let tab = &tab_manager.tabs[idx];
+        assert!(!tab.is_loading());
+        assert_eq!(tab.error, None);
+        assert_eq!(tab.content.as_text(), Some("<h1>Hi</h1>"));
+        assert!(tab.content.response.duration.is_some());
     }
 
-    if parts.scheme() == "" || parts.scheme() == "https" {
-        let content = ureq::get(url).call()?.into_string()?;
-        return Ok(content);
+    #[test]
+    fn redirects_update_the_url_and_security_of_the_tab() {
+        let fetcher = MockFetcher::new()
+            .redirect("http://example.com/", 301, "https://example.com/")
+            .page("https://example.com/", "text/html", "Secure");
+        let (mut tab_manager, receiver) = tab_manager(fetcher);
+
+        let idx = tab_manager.open("Example", "http://example.com/");
+        assert!(!tab_manager.tabs[idx].secure);
+
+        finish_next_load(&mut tab_manager, &receiver);
+        let tab = &tab_manager.tabs[idx];
+        assert_eq!(tab.url, "https://example.com/");
+        assert_eq!(tab.history[tab.history_idx].url, "https://example.com/");
+        assert!(tab.secure);
     }
-    if parts.scheme() == "" || parts.scheme() == "http" {
-        log::warn!("Opening insecure connection to {}", url);
-        let content = ureq::get(url).call()?.into_string()?;
-        return Ok(content);
+
+    #[test]
+    fn failed_loads_show_an_error_page() {
+        let fetcher = MockFetcher::new().status("https://example.com/", 500, "Server Error");
+        let (mut tab_manager, receiver) = tab_manager(fetcher);
+
+        let idx = tab_manager.open("Example", "https://example.com/");
+        finish_next_load(&mut tab_manager, &receiver);
+
+        let tab = &tab_manager.tabs[idx];
+        assert_eq!(
+            tab.error,
+            Some(LoadError::HttpStatus(500, "Server Error".into()))
+        );
+        assert!(tab.content.as_text().unwrap().contains("gosub://error"));
     }
 
-    // Always assume no protocol defaults to HTTPS://
+    #[test]
+    fn cancelled_loads_are_discarded() {
+        let fetcher = MockFetcher::new()
+            .page("https://example.com/", "text/html", "Too late")
+            .delay("https://example.com/", Duration::from_millis(50));
+        let (mut tab_manager, receiver) = tab_manager(fetcher);
 
-    Ok("Unknown protocol".into())
-}
+        let idx = tab_manager.open("Example", "https://example.com/");
+        assert!(tab_manager.cancel_load(idx));
+        assert!(!tab_manager.cancel_load(idx));
 
-fn process_gosub_protocol(url: Url) -> Result<String, Error> {
-    match url.host_str() {
-        Some("blank") => return Ok("This page is left intentionally blank".into()),
-        Some("help") => return Ok(gosub_help()),
-        Some("credits") => return Ok("Here be credits for the gosub engine".into()),
-        Some("settings") => return Ok("Here you can tinker with all kinds of dive and gosub settings".into()),
-        _ => return Ok("Unknown gosub protocol".into()),
+        assert_eq!(finish_next_load(&mut tab_manager, &receiver), None);
+        assert_ne!(tab_manager.tabs[idx].content.as_text(), Some("Too late"));
     }
-}
 
-fn gosub_help() -> String {
-    return r#"<h1>gosub://help</h1>
+    #[test]
+    fn navigating_back_and_forward_loads_from_history() {
+        let fetcher = MockFetcher::new()
+            .page("https://example.com/a", "text/html", "A")
+            .page("https://example.com/b", "text/html", "B");
+        let (mut tab_manager, receiver) = tab_manager(fetcher);
+
+        let idx = tab_manager.open("Example", "https://example.com/a");
+        finish_next_load(&mut tab_manager, &receiver);
+        assert!(tab_manager.navigate(idx, "https://example.com/b"));
+        finish_next_load(&mut tab_manager, &receiver);
+        assert_eq!(tab_manager.tabs[idx].content.as_text(), Some("B"));
+
+        assert!(tab_manager.back(idx));
+        finish_next_load(&mut tab_manager, &receiver);
+        assert_eq!(tab_manager.tabs[idx].content.as_text(), Some("A"));
+        assert!(!tab_manager.back(idx));
 
-    <p>This is the help page for the gosub engine</p>
+        assert!(tab_manager.forward(idx));
+        finish_next_load(&mut tab_manager, &receiver);
+        assert_eq!(tab_manager.tabs[idx].url, "https://example.com/b");
+    }
+
+    #[test]
+    fn finds_the_title_of_html_pages() {
+        let fetcher = MockFetcher::new()
+            .page(
+                "https://example.com/",
+                "text/html",
+                "<html><head><title>\n  Example\n  Domain </title></head></html>",
+            )
+            .page(
+                "https://example.com/title.txt",
+                "text/plain",
+                "<title>No</title>",
+            );
+        let (mut tab_manager, receiver) = tab_manager(fetcher);
+
+        let html = tab_manager.open("Html", "https://example.com/");
+        finish_next_load(&mut tab_manager, &receiver);
+        let text = tab_manager.open("Text", "https://example.com/title.txt");
+        finish_next_load(&mut tab_manager, &receiver);
+
+        assert_eq!(
+            tab_manager.tabs[html].page_title().as_deref(),
+            Some("Example Domain")
+        );
+        assert_eq!(tab_manager.tabs[text].page_title(), None);
+    }
 
-    <p>The following special gosub pages are supported:</p>
+    #[test]
+    fn restores_the_tabs_of_a_session() {
+        let fetcher = MockFetcher::new()
+            .page("https://example.com/a", "text/html", "A")
+            .page("https://example.com/b", "text/html", "B")
+            .page("https://example.com/c", "text/html", "C");
+        let (mut tab_manager, receiver) = tab_manager(fetcher.clone());
+
+        let idx = tab_manager.open("Example", "https://example.com/a");
+        finish_next_load(&mut tab_manager, &receiver);
+        tab_manager.tabs[idx].scroll = 7;
+        tab_manager.navigate(idx, "https://example.com/b");
+        finish_next_load(&mut tab_manager, &receiver);
+        tab_manager.tabs[idx].scroll = 3;
+        tab_manager.open("Other", "https://example.com/c");
+        finish_next_load(&mut tab_manager, &receiver);
+        tab_manager.current = 0;
+
+        let session = tab_manager.session("docs");
+        let (mut restored, receiver) = self::tab_manager(fetcher);
+        assert_eq!(restored.restore(&session), 2);
+        assert_eq!(restored.current, 0);
+        assert_eq!(restored.session("docs"), session);
+
+        finish_next_load(&mut restored, &receiver);
+        finish_next_load(&mut restored, &receiver);
+        let tab = &restored.tabs[0];
+        assert_eq!(tab.content.as_text(), Some("B"));
+        assert_eq!(tab.scroll, 3);
+        assert_eq!(tab.history[0].scroll, 7);
+    }
 
-    <table>
-      <tr><td><a target="_blank"href="gosub://blank">gosub://blank</td><td>Opens a blank page</td></tr>
-      <tr><td><a target="_blank"href="gosub://help">gosub://help</td><td>Displays this help page</td></tr>
-      <tr><td><a target="_blank" href="gosub://credits">gosub://credits</td><td>Displays credits of the Dive Browser and the Gosub Engine</td></tr>
-      <tr><td><a target="_blank" href="gosub://settings">gosub://settings</td><td>Displays the settings page</td></tr>
-    </table>
-    "#.into();
+    #[test]
+    fn skips_invalid_tabs_when_restoring() {
+        let (mut tab_manager, _receiver) = tab_manager(MockFetcher::new());
+        let session = WorkspaceSession {
+            name: "docs".into(),
+            tabs: vec![
+                TabSession {
+                    name: "Empty".into(),
+                    history: vec![],
+                    history_idx: 0,
+                    scroll: 0,
+                },
+                TabSession {
+                    name: "Blank".into(),
+                    history: vec![HistoryEntry {
+                        url: "gosub://blank".into(),
+                        scroll: 0,
+                    }],
+                    history_idx: 4,
+                    scroll: 0,
+                },
+            ],
+            current: 5,
+        };
+
+        assert_eq!(tab_manager.restore(&session), 1);
+        assert_eq!(tab_manager.current, 0);
+        assert_eq!(tab_manager.tabs[0].history_idx, 0);
+        assert_eq!(tab_manager.tabs[0].url, "gosub://blank");
+    }
+
+    #[test]
+    fn saves_content_under_a_safe_file_name() {
+        let fetcher = MockFetcher::new().page(
+            "https://example.com/my%20file%2F..%2Fnotes%0A.txt",
+            "text/plain",
+            "Notes",
+        );
+        let (mut tab_manager, receiver) = tab_manager(fetcher);
+        let idx = tab_manager.open("Notes", "https://example.com/my%20file%2F..%2Fnotes%0A.txt");
+        finish_next_load(&mut tab_manager, &receiver);
+
+        let dir = std::env::temp_dir().join(format!("dive-save-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = tab_manager.tabs[idx].save_content(&dir).unwrap();
+
+        assert_eq!(path, dir.join("my file..notes.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Notes");
+
+        // Existing files are left alone
+        fs::write(dir.join("my file..notes.txt.1"), "Old").unwrap();
+        let path = tab_manager.tabs[idx].save_content(&dir).unwrap();
+        assert_eq!(path, dir.join("my file..notes.txt.2"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Notes");
+        assert_eq!(
+            fs::read_to_string(dir.join("my file..notes.txt.1")).unwrap(),
+            "Old"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,161 @@
+use crate::dive::bookmark_manager::BookmarkManager;
+use crate::dive::settings::Settings;
+use url::form_urlencoded::byte_serialize;
+use url::Url;
+
+/// Schemes we can load, anything else typed in the URL bar is not taken as an url
+const SCHEMES: [&str; 6] = ["http", "https", "file", "data", "gosub", "view-source"];
+
+/// Turns what has been typed in the URL bar into the url to load. In order:
+///  - a bookmark keyword followed by search terms, like "gh rust", expands into the url of that
+///    bookmark, with %s replaced by the terms
+///  - complete urls are left alone
+///  - text with spaces, or a single word without a dot, is searched for
+///  - anything else is taken as a hostname, like "example.com/docs", and loaded over HTTPS
+///
+/// Returns None when nothing has been typed.
+pub fn resolve_url_input(
+    input: &str,
+    settings: &Settings,
+    bookmarks: &BookmarkManager,
+) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    let (keyword, terms) = match input.split_once(char::is_whitespace) {
+        Some((keyword, terms)) => (keyword, terms.trim()),
+        None => (input, ""),
+    };
+    if let Some(bookmark) = bookmarks.find_keyword(keyword) {
+        if bookmark.url.contains("%s") {
+            return Some(substitute(&bookmark.url, terms));
+        }
+        if terms.is_empty() {
+            return Some(bookmark.url);
+        }
+    }
+
+    if let Ok(url) = Url::parse(input) {
+        if SCHEMES.contains(&url.scheme()) {
+            return Some(input.to_string());
+        }
+    }
+
+    if !input.contains(char::is_whitespace) && is_host_like(input) {
+        if let Ok(url) = Url::parse(&format!("https://{}", input)) {
+            return Some(url.to_string());
+        }
+    }
+
+    Some(substitute(&settings.search_engine, input))
+}
+
+/// Returns true when the input starts with something that looks like a hostname, like
+/// "example.com", "localhost:8080" or "127.0.0.1"
+fn is_host_like(input: &str) -> bool {
+    let host = input.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or_default();
+    if host.starts_with('[') {
+        return true;
+    }
+    let host = host.split(':').next().unwrap_or_default();
+
+    host.eq_ignore_ascii_case("localhost")
+        || (host.contains('.')
+            && !host.starts_with('.')
+            && !host.ends_with('.')
+            && host
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '.' || c == '-'))
+}
+
+/// Replaces %s in the given url template with the url encoded search terms
+fn substitute(template: &str, terms: &str) -> String {
+    let terms = byte_serialize(terms.as_bytes()).collect::<String>();
+    template.replace("%s", &terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dive::bookmark_manager::Bookmark;
+    use uuid::Uuid;
+
+    fn resolve(input: &str) -> Option<String> {
+        let mut bookmarks = BookmarkManager::empty();
+        for (keyword, url) in [
+            ("gh", "https://github.com/search?q=%s"),
+            ("dive", "https://github.com/gosub-browser/gosub-dive"),
+        ] {
+            bookmarks.root.bookmarks.push(Bookmark {
+                title: keyword.into(),
+                url: url.into(),
+                tags: vec![],
+                keywords: vec![keyword.into()],
+                last_visited: 0,
+                id: Uuid::new_v4(),
+            });
+        }
+
+        resolve_url_input(input, &Settings::default(), &bookmarks)
+    }
+
+    #[test]
+    fn keeps_complete_urls() {
+        assert_eq!(
+            resolve("https://example.com/a b").as_deref(),
+            Some("https://example.com/a b")
+        );
+        assert_eq!(resolve("gosub://help").as_deref(), Some("gosub://help"));
+        assert_eq!(resolve(" file:///tmp/ ").as_deref(), Some("file:///tmp/"));
+    }
+
+    #[test]
+    fn adds_https_to_hostnames() {
+        assert_eq!(
+            resolve("example.com").as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(
+            resolve("localhost:8080/status?full=1").as_deref(),
+            Some("https://localhost:8080/status?full=1")
+        );
+        assert_eq!(resolve("127.0.0.1").as_deref(), Some("https://127.0.0.1/"));
+    }
+
+    #[test]
+    fn searches_for_anything_else() {
+        assert_eq!(
+            resolve("rust borrow checker").as_deref(),
+            Some("https://duckduckgo.com/?q=rust+borrow+checker")
+        );
+        assert_eq!(
+            resolve("ratatui").as_deref(),
+            Some("https://duckduckgo.com/?q=ratatui")
+        );
+        assert_eq!(
+            resolve("what is 1+1?").as_deref(),
+            Some("https://duckduckgo.com/?q=what+is+1%2B1%3F")
+        );
+        assert_eq!(resolve("   "), None);
+    }
+
+    #[test]
+    fn expands_bookmark_keywords() {
+        assert_eq!(
+            resolve("gh rust tui").as_deref(),
+            Some("https://github.com/search?q=rust+tui")
+        );
+        assert_eq!(
+            resolve("dive").as_deref(),
+            Some("https://github.com/gosub-browser/gosub-dive")
+        );
+        // Keywords without a %s in their url don't take search terms
+        assert_eq!(
+            resolve("dive into rust").as_deref(),
+            Some("https://duckduckgo.com/?q=dive+into+rust")
+        );
+    }
+}
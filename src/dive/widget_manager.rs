@@ -18,8 +18,6 @@ pub trait Drawable {
 pub struct Widget {
     /// Unique identifier for this widget
     pub id: String,
-    /// 0 is the highest, 255 is the lowest
-    pub priority: u8,
     /// Does this object need to be rendered
     pub visible: bool,
     /// Actual object with rendering and event handling
@@ -27,10 +25,9 @@ pub struct Widget {
 }
 
 impl Widget {
-    pub fn new(id: &str, priority: u8, visible: bool, inner: Rc<RefCell<dyn Drawable>>) -> Self {
+    pub fn new(id: &str, visible: bool, inner: Rc<RefCell<dyn Drawable>>) -> Self {
         Self {
             id: id.into(),
-            priority,
             visible,
             inner,
         }
@@ -143,10 +143,8 @@ impl Drawable for BookmarkListWidget {
                     id: "bookmark_list".into(),
                 });
             }
-            KeyCode::Right => {
-                if self.selection == Selection::Tree {
-                    self.tree_state.key_right();
-                }
+            KeyCode::Right if self.selection == Selection::Tree => {
+                self.tree_state.key_right();
             }
             KeyCode::Down => {
                 if self.selection == Selection::Tree {
@@ -168,10 +166,8 @@ impl Drawable for BookmarkListWidget {
                     }
                 }
             }
-            KeyCode::Left => {
-                if self.selection == Selection::Tree {
-                    self.tree_state.key_left();
-                }
+            KeyCode::Left if self.selection == Selection::Tree => {
+                self.tree_state.key_left();
             }
             KeyCode::Enter => {
                 // let sel = self.state.selected().unwrap_or(0);
@@ -190,10 +186,8 @@ impl Drawable for BookmarkListWidget {
                     self.table_state.select(Some(0));
                 }
             }
-            Char(' ') => {
-                if self.selection == Selection::Tree {
-                    self.tree_state.toggle_selected();
-                }
+            Char(' ') if self.selection == Selection::Tree => {
+                self.tree_state.toggle_selected();
             }
             Char('/') => {
                 // search for tags
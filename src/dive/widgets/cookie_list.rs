@@ -0,0 +1,199 @@
+use crate::dive::command_queue::{Command, CommandQueue};
+use crate::dive::cookies::{CookieInfo, CookieJar};
+use crate::dive::ui::centered_rect;
+use crate::dive::widget_manager::Drawable;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+use std::sync::{Arc, Mutex};
+
+/// Popup that lists the cookies in the jar. Typing filters the list on domain and name.
+pub struct CookieListWidget {
+    cookies: Arc<Mutex<CookieJar>>,
+    filter: String,
+    state: TableState,
+    /// Cookies that matched the filter at the last render
+    visible: Vec<CookieInfo>,
+}
+
+impl CookieListWidget {
+    pub fn new(cookies: Arc<Mutex<CookieJar>>) -> Self {
+        Self {
+            cookies,
+            filter: String::new(),
+            state: TableState::default(),
+            visible: vec![],
+        }
+    }
+
+    fn refresh(&mut self) {
+        let filter = self.filter.to_lowercase();
+        self.visible = self
+            .cookies
+            .lock()
+            .expect("cookie jar lock")
+            .cookies()
+            .into_iter()
+            .filter(|cookie| {
+                cookie.domain.to_lowercase().contains(&filter)
+                    || cookie.name.to_lowercase().contains(&filter)
+            })
+            .collect();
+
+        let sel = match self.state.selected() {
+            _ if self.visible.is_empty() => None,
+            Some(sel) => Some(sel.min(self.visible.len() - 1)),
+            None => Some(0),
+        };
+        self.state = self.state.clone().with_selected(sel);
+    }
+
+    fn select(&mut self, offset: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let sel = self
+            .state
+            .selected()
+            .unwrap_or(0)
+            .saturating_add_signed(offset)
+            .min(self.visible.len() - 1);
+        self.state = self.state.clone().with_selected(Some(sel));
+    }
+
+    fn selected(&self) -> Option<CookieInfo> {
+        self.visible.get(self.state.selected()?).cloned()
+    }
+}
+
+fn flags(cookie: &CookieInfo) -> String {
+    let mut flags = vec![];
+    if cookie.secure {
+        flags.push("Secure");
+    }
+    if cookie.http_only {
+        flags.push("HttpOnly");
+    }
+    flags.join(" ")
+}
+
+impl Drawable for CookieListWidget {
+    fn on_show(&mut self) {
+        self.filter.clear();
+        self.state = TableState::default();
+        self.refresh();
+    }
+
+    fn on_hide(&mut self) {}
+
+    fn render(&mut self, f: &mut Frame) {
+        self.refresh();
+
+        let area = centered_rect(90, 70, f.size());
+        f.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!("Cookies ({})", self.visible.len()))
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let filter = Paragraph::new(Line::from(vec![
+            Span::styled("Filter: ", Style::default().fg(Color::Yellow)),
+            Span::raw(self.filter.as_str()),
+        ]));
+        f.render_widget(filter, chunks[0]);
+
+        let rows = self.visible.iter().map(|cookie| {
+            Row::new(vec![
+                cookie.domain.clone(),
+                cookie.name.clone(),
+                cookie.value.clone(),
+                cookie.path.clone(),
+                match cookie.expires {
+                    Some(expires) => expires.format("%Y-%m-%d %H:%M").to_string(),
+                    None => "Session".into(),
+                },
+                flags(cookie),
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(20),
+                Constraint::Percentage(15),
+                Constraint::Percentage(25),
+                Constraint::Percentage(10),
+                Constraint::Length(16),
+                Constraint::Length(15),
+            ],
+        )
+        .header(Row::new(vec!["Domain", "Name", "Value", "Path", "Expires", "Flags"]).bold())
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().bg(Color::Red).add_modifier(Modifier::BOLD));
+        f.render_stateful_widget(table, chunks[1], &mut self.state);
+
+        let help =
+            Paragraph::new("DEL delete cookie  CTRL-D delete all cookies of the domain  ESC close")
+                .style(Style::default().fg(Color::DarkGray));
+        f.render_widget(help, chunks[2]);
+    }
+
+    fn event_handler(
+        &mut self,
+        queue: &mut CommandQueue,
+        key: KeyEvent,
+    ) -> anyhow::Result<Option<KeyEvent>> {
+        match key.code {
+            KeyCode::Esc => {
+                queue.push(Command::DestroyWidget {
+                    id: "cookie_list".into(),
+                });
+            }
+            KeyCode::Down => self.select(1),
+            KeyCode::Up => self.select(-1),
+            KeyCode::PageDown => self.select(10),
+            KeyCode::PageUp => self.select(-10),
+            KeyCode::Delete => {
+                if let Some(cookie) = self.selected() {
+                    self.cookies
+                        .lock()
+                        .expect("cookie jar lock")
+                        .remove(&cookie);
+                    self.refresh();
+                }
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(cookie) = self.selected() {
+                    self.cookies
+                        .lock()
+                        .expect("cookie jar lock")
+                        .remove_domain(&cookie.domain);
+                    self.refresh();
+                }
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.refresh();
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.refresh();
+            }
+            _ => {}
+        }
+
+        Ok(Some(key))
+    }
+}
@@ -18,9 +18,9 @@ This is the help screen for Gosub Dive. It is a work in progress and displays th
  #2-------------
   #1F1#0      Display this help screen
   #1F2#0      Opens tab list
-  #1F3#0
-  #1F4#0
-  #1F5#0
+  #1F3#0      Opens cookie manager
+  #1F4#0      Opens page info
+  #1F5#0      Reload current page
   #1F6#0      Opens log screen
   #1F7#0      Opens history menu
   #1F8#0      Opens bookmark menu
@@ -30,20 +30,49 @@ This is the help screen for Gosub Dive. It is a work in progress and displays th
  #2----------
   #1CTRL-N#0    Opens new tab with blank page
   #1CTRL-G#0    Asks for an URL to open
+  #1CTRL-L#0    Asks for an URL to open in the current tab
+                TAB or UP/DOWN in the URL prompt cycles through suggestions
   #1CTRL-B#0    Browse back to previous page
+  #1ALT-LEFT#0  Browse back to previous page
+  #1ALT-RIGHT#0 Browse forward to next page
+  #1N ENTER#0   Follow link number N in the current tab
+  #1N ALT-ENTER#0 Follow link number N in a new tab
   #1CTRL-R#0    Reload current page
+  #1ALT-R#0     Reload current page, bypassing any caches
   #1CTRL-W#0    Close current tab
+  #1ESC#0       Stop loading current page
+
+ #2Scrolling
+ #2---------
+  #1UP/DOWN#0   Scroll one line up or down (also k and j)
+  #1PGUP/PGDN#0 Scroll one page up or down (also b and SPACE)
+  #1CTRL-U/D#0  Scroll half a page up or down
+  #1HOME/END#0  Go to the top or bottom of the page (also g and G)
+
+ #2Finding text
+ #2------------
+  #1/#0         Find text in the current page (also CTRL-F)
+  #1n / N#0     Jump to the next or previous match
+  #1ESC#0       Stop finding and remove the highlights
 
  #2General commands
  #2----------------
+  #1s#0         Save the current page to disk
+  #1ALT-U#0     View the source of the current page in a new tab
+  #1ALT-C#0     Clear the HTTP cache
   #1CTRL-Q#0    Quit Gosub Dive
 
  #2Tab management
  #2--------------
   #1ALT-0..9#0  Switch to tab 0..9
-  #1CTRL-I#0    Rename tab
+  #1CTRL-E#0    Rename tab
   #1TAB#0       Switch to next tab
 
+ #2Workspaces
+ #2----------
+  #1ALT-W#0     Opens workspace picker, to switch between sets of tabs
+                n creates, r renames and DEL deletes a workspace in the picker
+
 "#;
 
 fn generate_lines_from_helptext() -> Vec<Line<'static>> {
@@ -0,0 +1,336 @@
+use crate::dive::command_queue::{Command, CommandQueue};
+use crate::dive::history_manager::{HistoryItem, HistoryManager};
+use crate::dive::ui::centered_rect;
+use crate::dive::widget_manager::Drawable;
+use chrono::{Days, Local, NaiveDate};
+use crossterm::event::KeyCode::Char;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::prelude::{Color, Modifier, Style};
+use ratatui::widgets::TableState;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Selection {
+    // List of days is currently in focus
+    Days,
+    // Pages visited on the selected day are currently in focus
+    Table,
+}
+
+/// Browsing history, grouped by the day pages have last been visited
+pub struct HistoryListWidget {
+    history_manager: Rc<RefCell<HistoryManager>>,
+    /// Tab that is current, where pages are opened in
+    tab_idx: usize,
+    days_state: ListState,
+    table_state: TableState,
+    selection: Selection,
+    /// Only pages with this text in their url or title are listed
+    query: String,
+    /// Set while the query is being typed
+    searching: bool,
+    /// Pages that match the query, grouped per day, the most recent day first
+    days: Vec<(NaiveDate, Vec<HistoryItem>)>,
+    /// Query and version of the history the days have been grouped for
+    days_for: Option<(String, u64)>,
+}
+
+impl HistoryListWidget {
+    pub fn new(history_manager: Rc<RefCell<HistoryManager>>, tab_idx: usize) -> Self {
+        Self {
+            history_manager,
+            tab_idx,
+            days_state: ListState::default().with_selected(Some(0)),
+            table_state: TableState::default(),
+            selection: Selection::Days,
+            query: String::new(),
+            searching: false,
+            days: vec![],
+            days_for: None,
+        }
+    }
+
+    /// Groups the pages that match the query per day again, when the query or the history
+    /// changed since the last time
+    fn refresh_days(&mut self) {
+        let history_manager = self.history_manager.borrow();
+        let version = history_manager.version();
+        if self
+            .days_for
+            .as_ref()
+            .is_some_and(|(query, v)| *query == self.query && *v == version)
+        {
+            return;
+        }
+
+        self.days.clear();
+        for item in history_manager.search(&self.query) {
+            match self.days.last_mut() {
+                Some((day, items)) if *day == item.day() => items.push(item.clone()),
+                _ => self.days.push((item.day(), vec![item.clone()])),
+            }
+        }
+        self.days_for = Some((self.query.clone(), version));
+    }
+
+    /// Returns the selected day with its pages, if any
+    fn selected_day(&self) -> Option<&(NaiveDate, Vec<HistoryItem>)> {
+        self.days.get(self.days_state.selected().unwrap_or(0))
+    }
+
+    fn selected_item(&self) -> Option<&HistoryItem> {
+        let (_, items) = self.selected_day()?;
+        items.get(self.table_state.selected()?)
+    }
+
+    /// Keeps the selections within the days and pages that are listed
+    fn clamp_selection(&mut self) {
+        self.refresh_days();
+        let days = &self.days;
+        let day = self
+            .days_state
+            .selected()
+            .unwrap_or(0)
+            .min(days.len().saturating_sub(1));
+        self.days_state.select(Some(day));
+
+        let items = days.get(day).map_or(0, |(_, items)| items.len());
+        if items == 0 {
+            self.table_state.select(None);
+            self.selection = Selection::Days;
+        } else if let Some(sel) = self.table_state.selected() {
+            self.table_state.select(Some(sel.min(items - 1)));
+        }
+    }
+
+    fn select(&mut self, offset: isize) {
+        match self.selection {
+            Selection::Days => {
+                let sel = self.days_state.selected().unwrap_or(0);
+                self.days_state
+                    .select(Some(sel.saturating_add_signed(offset)));
+                self.table_state.select(None);
+            }
+            Selection::Table => {
+                let sel = self.table_state.selected().unwrap_or(0);
+                self.table_state
+                    .select(Some(sel.saturating_add_signed(offset)));
+            }
+        }
+        self.clamp_selection();
+    }
+
+    fn open(&self, queue: &mut CommandQueue, new_tab: bool) {
+        let Some(item) = self.selected_item().cloned() else {
+            return;
+        };
+
+        if new_tab {
+            let title = if item.title.is_empty() {
+                item.url.clone()
+            } else {
+                item.title.clone()
+            };
+            queue.push(Command::NewTabUrl {
+                title,
+                url: item.url,
+            });
+        } else {
+            queue.push(Command::NavigateTab {
+                idx: self.tab_idx,
+                url: item.url,
+            });
+        }
+        queue.push(Command::DestroyWidget {
+            id: "history_list".into(),
+        });
+    }
+}
+
+fn day_name(day: NaiveDate) -> String {
+    let today = Local::now().date_naive();
+    if day == today {
+        "Today".into()
+    } else if today.checked_sub_days(Days::new(1)) == Some(day) {
+        "Yesterday".into()
+    } else {
+        day.format("%a %Y-%m-%d").to_string()
+    }
+}
+
+impl Drawable for HistoryListWidget {
+    fn on_show(&mut self) {
+        self.clamp_selection();
+    }
+
+    fn on_hide(&mut self) {}
+
+    fn render(&mut self, f: &mut Frame) {
+        let area = centered_rect(100, 75, f.size());
+        f.render_widget(Clear, area);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+            .split(rows[0]);
+
+        self.refresh_days();
+        let days = &self.days;
+        let day_items = days
+            .iter()
+            .map(|(day, items)| ListItem::new(format!("{} ({})", day_name(*day), items.len())))
+            .collect::<Vec<_>>();
+
+        let title = if self.query.is_empty() {
+            "History".to_string()
+        } else {
+            format!("History matching '{}'", self.query)
+        };
+        let mut list = List::new(day_items).block(
+            Block::default()
+                .title(title)
+                .borders(Borders::LEFT | Borders::BOTTOM | Borders::TOP),
+        );
+
+        let selected = self.days_state.selected().unwrap_or(0);
+        let table_rows = days
+            .get(selected)
+            .map(|(_, items)| items.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|item| {
+                Row::new(vec![
+                    item.last_visited().format("%H:%M").to_string(),
+                    item.title.clone(),
+                    item.url.clone(),
+                    item.visit_count.to_string(),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let widths = [
+            Constraint::Length(6),
+            Constraint::Percentage(35),
+            Constraint::Percentage(50),
+            Constraint::Length(6),
+        ];
+        let mut table = Table::new(table_rows, widths)
+            .block(Block::default().borders(Borders::ALL))
+            .header(
+                Row::new(vec!["Time", "Title", "URL", "Visits"])
+                    .style(Style::default().fg(Color::Yellow))
+                    .bottom_margin(1),
+            );
+
+        // Highlight the selected widget (days or table)
+        let active = Style::new()
+            .fg(Color::Yellow)
+            .bg(Color::Red)
+            .add_modifier(Modifier::BOLD);
+        if self.selection == Selection::Days {
+            list = list.highlight_style(active);
+        } else {
+            list = list.highlight_style(Style::new().fg(Color::Black).bg(Color::Gray));
+            table = table.highlight_style(active);
+        }
+
+        let help = if self.searching {
+            format!("Search: {}_   (ENTER to keep, ESC to clear)", self.query)
+        } else {
+            "TAB switch pane  / search  ENTER open  ALT-ENTER open in new tab  DEL delete  ESC close"
+                .into()
+        };
+
+        f.render_stateful_widget(list, layout[0], &mut self.days_state);
+        f.render_stateful_widget(table, layout[1], &mut self.table_state);
+        f.render_widget(
+            Paragraph::new(help).style(Style::default().bg(Color::Blue).fg(Color::White)),
+            rows[1],
+        );
+    }
+
+    fn event_handler(
+        &mut self,
+        queue: &mut CommandQueue,
+        key: KeyEvent,
+    ) -> anyhow::Result<Option<KeyEvent>> {
+        if self.searching {
+            match key.code {
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.searching = false;
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                Char(c) => self.query.push(c),
+                _ => {}
+            }
+            self.clamp_selection();
+
+            return Ok(Some(key));
+        }
+
+        match key.code {
+            KeyCode::Esc => {
+                queue.push(Command::DestroyWidget {
+                    id: "history_list".into(),
+                });
+            }
+            KeyCode::Down => self.select(1),
+            KeyCode::Up => self.select(-1),
+            KeyCode::PageDown => self.select(10),
+            KeyCode::PageUp => self.select(-10),
+            KeyCode::Tab => {
+                // Switch between the days and the pages of the selected day
+                if self.selection == Selection::Days {
+                    self.selection = Selection::Table;
+                    self.table_state.select(Some(0));
+                } else {
+                    self.selection = Selection::Days;
+                    self.table_state.select(None);
+                }
+                self.clamp_selection();
+            }
+            KeyCode::Enter if self.selection == Selection::Table => {
+                let new_tab = key.modifiers.contains(KeyModifiers::ALT);
+                self.open(queue, new_tab);
+            }
+            KeyCode::Enter => {
+                self.selection = Selection::Table;
+                self.table_state.select(Some(0));
+                self.clamp_selection();
+            }
+            KeyCode::Delete => {
+                match self.selection {
+                    // Only the pages that are listed for the day, when searching
+                    Selection::Days => {
+                        if let Some((_, items)) = self.selected_day() {
+                            let ids = items.iter().map(|item| item.id).collect::<Vec<_>>();
+                            self.history_manager.borrow_mut().remove(&ids);
+                        }
+                    }
+                    Selection::Table => {
+                        if let Some(id) = self.selected_item().map(|item| item.id) {
+                            self.history_manager.borrow_mut().remove(&[id]);
+                        }
+                    }
+                }
+                self.clamp_selection();
+            }
+            Char('/') => self.searching = true,
+            _ => {}
+        }
+
+        Ok(Some(key))
+    }
+}
@@ -19,6 +19,8 @@ pub struct InputWidget {
 pub enum InputSubmitCommand {
     RenameTab { tab_idx: usize },
     OpenTabWithUrl,
+    NavigateTabToUrl { tab_idx: usize },
+    Search { tab_idx: usize },
 }
 
 impl InputWidget {
@@ -30,6 +32,23 @@ impl InputWidget {
             command,
         }
     }
+
+    pub fn value(&self) -> &str {
+        self.input.value()
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.input = Input::new(value.into());
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.into();
+    }
+
+    /// Returns the area of the screen the prompt is drawn in
+    pub fn area(&self, screen: Rect) -> Rect {
+        centered_rect_fixed(self.max_size as u16, 5, screen)
+    }
 }
 
 impl Drawable for InputWidget {
@@ -52,7 +71,7 @@ impl Drawable for InputWidget {
             .style(Style::default().bg(Color::Blue).fg(Color::Yellow))
             .block(block);
 
-        let area = centered_rect_fixed(self.max_size as u16, 5, f.size());
+        let area = self.area(f.size());
         f.render_widget(Clear, area);
         f.render_widget(input, area);
 
@@ -1,10 +1,16 @@
 pub mod bookmark_list;
+pub mod cookie_list;
 pub mod help;
+pub mod history_list;
 pub mod input;
 pub mod log;
 pub mod menu_bar;
+pub mod page_info;
+pub mod search;
 pub mod splash;
 pub mod status_bar;
 pub mod tab_list;
 pub mod tabs;
 pub mod test;
+pub mod url_input;
+pub mod workspace_list;
@@ -0,0 +1,175 @@
+use crate::dive::command_queue::{Command, CommandQueue};
+use crate::dive::loader::format_size;
+use crate::dive::tab_manager::{Tab, TabManager};
+use crate::dive::ui::centered_rect;
+use crate::dive::widget_manager::Drawable;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Cell, Clear, Padding, Row, Table, TableState};
+use ratatui::Frame;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Popup that shows how the page in the current tab has been loaded
+pub struct PageInfoWidget {
+    pub tab_manager: Rc<RefCell<TabManager>>,
+    pub state: TableState,
+    /// Number of rows in the table at the last render
+    rows: usize,
+}
+
+impl PageInfoWidget {
+    pub fn new(tab_manager: Rc<RefCell<TabManager>>) -> Self {
+        Self {
+            tab_manager,
+            state: TableState::default().with_selected(Some(0)),
+            rows: 0,
+        }
+    }
+
+    fn select(&mut self, offset: isize) {
+        let sel = self
+            .state
+            .selected()
+            .unwrap_or(0)
+            .saturating_add_signed(offset);
+        let sel = sel.min(self.rows.saturating_sub(1));
+        self.state = self.state.clone().with_selected(Some(sel));
+    }
+}
+
+/// Returns the rows of the table with the details of the given tab
+fn info_rows(tab: &Tab) -> Vec<(String, String)> {
+    let content = &tab.content;
+    let response = &content.response;
+
+    let mut rows = vec![
+        ("URL".to_string(), tab.url.clone()),
+        (
+            "Final URL".into(),
+            response
+                .final_url
+                .clone()
+                .unwrap_or_else(|| tab.url.clone()),
+        ),
+    ];
+
+    for (idx, redirect) in response.redirects.iter().enumerate() {
+        rows.push((
+            format!("Redirect {}", idx + 1),
+            format!("{} from {}", redirect.status, redirect.url),
+        ));
+    }
+
+    rows.extend([
+        (
+            "Status".into(),
+            match (&response.status, &tab.error) {
+                (Some((code, text)), _) => format!("{} {}", code, text),
+                (None, Some(error)) => error.to_string(),
+                (None, None) => "-".into(),
+            },
+        ),
+        ("MIME type".into(), content.mime_type.clone()),
+        (
+            "Encoding".into(),
+            content.encoding.clone().unwrap_or_else(|| "-".into()),
+        ),
+        (
+            "Content length".into(),
+            // Content generated by dive itself was never received, so it has no size
+            match response.size {
+                Some(size) => format!("{} ({} bytes)", format_size(size), size),
+                None => "-".into(),
+            },
+        ),
+        (
+            "Load time".into(),
+            match response.duration {
+                Some(duration) => format!("{} ms", duration.as_millis()),
+                None => "-".into(),
+            },
+        ),
+        (
+            "From cache".into(),
+            if response.from_cache { "yes" } else { "no" }.into(),
+        ),
+        (
+            "Secure".into(),
+            if tab.secure { "yes" } else { "no" }.into(),
+        ),
+    ]);
+
+    for (name, value) in &response.headers {
+        rows.push((name.clone(), value.clone()));
+    }
+
+    rows
+}
+
+impl Drawable for PageInfoWidget {
+    fn on_show(&mut self) {}
+    fn on_hide(&mut self) {}
+
+    fn render(&mut self, f: &mut Frame) {
+        let (info, header_count) = {
+            let tab_manager = self.tab_manager.borrow();
+            let tab = tab_manager.current();
+            (info_rows(tab), tab.content.response.headers.len())
+        };
+
+        self.rows = info.len();
+
+        let mut rows = vec![];
+        for (idx, (name, value)) in info.into_iter().enumerate() {
+            // Response headers are shown below the general details
+            let style = if idx >= self.rows - header_count {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::Yellow)
+            };
+            rows.push(Row::new(vec![
+                Cell::from(name).style(style),
+                Cell::from(value),
+            ]));
+        }
+
+        let block = Block::default()
+            .title("Page Info")
+            .borders(Borders::ALL)
+            .padding(Padding::new(1, 1, 1, 1));
+
+        let table = Table::new(rows, [Constraint::Length(24), Constraint::Min(10)])
+            .style(Style::default().fg(Color::White))
+            .highlight_style(Style::default().bg(Color::Red).add_modifier(Modifier::BOLD))
+            .block(block);
+
+        let area = centered_rect(80, 60, f.size());
+        f.render_widget(Clear, area);
+
+        f.render_stateful_widget(table, area, &mut self.state);
+    }
+
+    fn event_handler(
+        &mut self,
+        queue: &mut CommandQueue,
+        key: KeyEvent,
+    ) -> anyhow::Result<Option<KeyEvent>> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => {
+                queue.push(Command::DestroyWidget {
+                    id: "page_info".into(),
+                });
+            }
+            KeyCode::Down => self.select(1),
+            KeyCode::Up => self.select(-1),
+            KeyCode::PageDown => self.select(10),
+            KeyCode::PageUp => self.select(-10),
+            KeyCode::Home => self.select(isize::MIN),
+            KeyCode::End => self.select(isize::MAX),
+            _ => {}
+        }
+
+        Ok(Some(key))
+    }
+}
@@ -0,0 +1,90 @@
+use crate::dive::command_queue::{Command, CommandQueue};
+use crate::dive::search::SearchOptions;
+use crate::dive::widget_manager::Drawable;
+use crate::dive::widgets::input::{InputSubmitCommand, InputWidget};
+use crossterm::event::KeyCode::Char;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Frame;
+
+/// Search prompt for finding text in the current tab. The search is updated while typing.
+pub struct SearchWidget {
+    input: InputWidget,
+    tab_idx: usize,
+    options: SearchOptions,
+}
+
+impl SearchWidget {
+    pub fn new(tab_idx: usize, query: &str, options: SearchOptions) -> Self {
+        let mut widget = Self {
+            input: InputWidget::new("", query, 60, InputSubmitCommand::Search { tab_idx }),
+            tab_idx,
+            options,
+        };
+        widget.update_title();
+
+        widget
+    }
+
+    fn update_title(&mut self) {
+        let on_off = |b: bool| if b { "on" } else { "off" };
+
+        self.input.set_title(&format!(
+            "Find in page (ALT-C case: {}, ALT-R regex: {})",
+            on_off(self.options.case_sensitive),
+            on_off(self.options.regex)
+        ));
+    }
+
+    fn search(&self, queue: &mut CommandQueue) {
+        queue.push(Command::Search {
+            idx: self.tab_idx,
+            query: self.input.value().into(),
+            options: self.options,
+        });
+    }
+}
+
+impl Drawable for SearchWidget {
+    fn on_show(&mut self) {}
+    fn on_hide(&mut self) {}
+
+    fn render(&mut self, f: &mut Frame) {
+        self.input.render(f);
+    }
+
+    fn event_handler(
+        &mut self,
+        queue: &mut CommandQueue,
+        key: KeyEvent,
+    ) -> anyhow::Result<Option<KeyEvent>> {
+        match key.code {
+            Char('c') if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.options.case_sensitive = !self.options.case_sensitive;
+                self.update_title();
+                self.search(queue);
+            }
+            Char('r') if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.options.regex = !self.options.regex;
+                self.update_title();
+                self.search(queue);
+            }
+            KeyCode::Esc => {
+                queue.push(Command::ClearSearch { idx: self.tab_idx });
+                queue.push(Command::DestroyWidget { id: "input".into() });
+            }
+            // The search is already done while typing, so we only need to close the prompt
+            KeyCode::Enter => {
+                queue.push(Command::DestroyWidget { id: "input".into() });
+            }
+            _ => {
+                let before = self.input.value().to_string();
+                self.input.event_handler(queue, key)?;
+                if self.input.value() != before {
+                    self.search(queue);
+                }
+            }
+        }
+
+        Ok(Some(key))
+    }
+}
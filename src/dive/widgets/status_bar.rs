@@ -6,14 +6,20 @@ use ratatui::prelude::*;
 use ratatui::widgets::{Clear, Paragraph};
 
 pub struct TabInfo {
-    pub name: String,
     pub url: String,
     pub secure: bool,
+    pub loading: bool,
 }
 
 pub struct StatusBar {
     pub status: String,
     pub tab_info: Option<TabInfo>,
+    /// Name of the active workspace
+    pub workspace: Option<String>,
+    /// How far (in percent) the current page has been scrolled down
+    pub scroll_percentage: Option<u16>,
+    /// Status of the active search in the current page, like "3/17"
+    pub search_status: Option<String>,
 }
 
 impl StatusBar {
@@ -21,6 +27,9 @@ impl StatusBar {
         Self {
             status: "Press F1 for help".into(),
             tab_info: None,
+            workspace: None,
+            scroll_percentage: None,
+            search_status: None,
         }
     }
 
@@ -31,6 +40,18 @@ impl StatusBar {
     pub fn tab_info(&mut self, tab_info: Option<TabInfo>) {
         self.tab_info = tab_info;
     }
+
+    pub fn workspace(&mut self, workspace: Option<String>) {
+        self.workspace = workspace;
+    }
+
+    pub fn scroll_percentage(&mut self, percentage: Option<u16>) {
+        self.scroll_percentage = percentage;
+    }
+
+    pub fn search_status(&mut self, search_status: Option<String>) {
+        self.search_status = search_status;
+    }
 }
 
 impl Drawable for StatusBar {
@@ -40,29 +61,51 @@ impl Drawable for StatusBar {
     fn render(&mut self, f: &mut Frame) {
         let chunks = get_layout_chunks(f);
 
-        let status_bar = Paragraph::new(Line::from(vec![
+        let mut spans = vec![
             Span::styled(
                 self.status.clone(),
                 Style::default().add_modifier(Modifier::BOLD),
             ),
             Span::raw(" | "),
-            if let Some(tab_info) = &self.tab_info {
-                Span::styled(
-                    format!(
-                        "{} {}",
-                        if tab_info.secure { "🔒" } else { "  " },
-                        tab_info.url
-                    ),
-                    Style::default().add_modifier(Modifier::BOLD),
-                )
-            } else {
-                Span::styled(
-                    "No tabs open",
-                    Style::default().add_modifier(Modifier::BOLD),
-                )
-            },
-        ]))
-        .style(Style::default().bg(Color::Blue).bold());
+        ];
+
+        if let Some(workspace) = &self.workspace {
+            spans.push(Span::raw(format!("[{}] ", workspace)));
+        }
+
+        spans.push(if let Some(tab_info) = &self.tab_info {
+            Span::styled(
+                format!(
+                    "{} {}{}",
+                    if tab_info.secure { "🔒" } else { "  " },
+                    tab_info.url,
+                    if tab_info.loading {
+                        " (loading...)"
+                    } else {
+                        ""
+                    }
+                ),
+                Style::default().add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled(
+                "No tabs open",
+                Style::default().add_modifier(Modifier::BOLD),
+            )
+        });
+
+        if let Some(percentage) = self.scroll_percentage {
+            spans.push(Span::raw(" | "));
+            spans.push(Span::raw(format!("{}%", percentage)));
+        }
+
+        if let Some(search_status) = &self.search_status {
+            spans.push(Span::raw(" | "));
+            spans.push(Span::raw(format!("Find: {}", search_status)));
+        }
+
+        let status_bar =
+            Paragraph::new(Line::from(spans)).style(Style::default().bg(Color::Blue).bold());
 
         f.render_widget(Clear, chunks[2]);
         f.render_widget(status_bar, chunks[2]);
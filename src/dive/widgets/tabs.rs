@@ -1,44 +1,64 @@
-use crate::dive::command_queue::CommandQueue;
+use crate::dive::command_queue::{Command, CommandQueue};
 use crate::dive::tab_manager::TabManager;
 use crate::dive::ui::get_layout_chunks;
 use crate::dive::widget_manager::Drawable;
-use crossterm::event::KeyEvent;
+use crate::dive::widgets::status_bar::StatusBar;
+use crossterm::event::KeyCode::Char;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Layout, Rect};
-use ratatui::prelude::{Constraint, Direction, Stylize};
-use ratatui::widgets::{Block, Borders, Clear, ListState, Paragraph, Tabs, Widget, Wrap};
+use ratatui::prelude::{Color, Constraint, Direction, Style, Stylize};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{
+    Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Tabs,
+};
 use ratatui::Frame;
 use std::cell::RefCell;
 use std::rc::Rc;
-use ratatui::buffer::Buffer;
 
-#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
-pub struct BackgroundClear;
+pub struct TabsWidget {
+    pub tab_manager: Rc<RefCell<TabManager>>,
+    pub status_bar: Rc<RefCell<StatusBar>>,
+    /// Link number that is being typed in
+    pub link_number: String,
+    /// Height of the page content area at the last render
+    pub page_height: usize,
+}
 
-impl Widget for BackgroundClear {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        for x in area.left()..area.right() {
-            for y in area.top()..area.bottom() {
-                buf.get_mut(x, y).reset();
-                buf.get_mut(x, y).set_char('░');
-            }
+impl TabsWidget {
+    pub fn new(tab_manager: Rc<RefCell<TabManager>>, status_bar: Rc<RefCell<StatusBar>>) -> Self {
+        Self {
+            tab_manager,
+            status_bar,
+            link_number: String::new(),
+            page_height: 0,
         }
     }
 }
 
-pub struct TabsWidget {
-    pub tab_manager: Rc<RefCell<TabManager>>,
-    pub state: ListState,
-}
-
 impl TabsWidget {
-    pub fn new(tab_manager: Rc<RefCell<TabManager>>) -> Self {
-        let tm = tab_manager.clone();
-        let widget = Self {
-            tab_manager: tm.clone(),
-            state: ListState::default().with_selected(Some(tm.borrow().current)),
+    fn has_search(&self) -> bool {
+        self.tab_manager.borrow().current().search.is_some()
+    }
+
+    /// Selects the next (or previous) search match, and scrolls it into view
+    fn goto_match(&mut self, forward: bool) {
+        let mut tab_manager = self.tab_manager.borrow_mut();
+        let tab = tab_manager.current_mut();
+
+        let line = match tab.search.as_mut() {
+            Some(search) if forward => search.next(),
+            Some(search) => search.prev(),
+            None => None,
         };
+        if let Some(line) = line {
+            tab.reveal_line(line, self.page_height);
+        }
+    }
 
-        widget
+    fn scroll_by(&mut self, lines: isize) {
+        let mut tab_manager = self.tab_manager.borrow_mut();
+        let tab = tab_manager.current_mut();
+        tab.scroll = tab.scroll.saturating_add_signed(lines);
     }
 }
 
@@ -50,7 +70,8 @@ impl Drawable for TabsWidget {
         let mut tab_names = Vec::new();
         for (idx, tab) in self.tab_manager.borrow_mut().tabs.iter().enumerate() {
             tab_names.push(format!(
-                " {} {}:{} ",
+                " {}{} {}:{} ",
+                if tab.is_loading() { "⏳" } else { "" },
                 if tab.secure { "🔒" } else { "" },
                 idx,
                 tab.name.clone()
@@ -73,21 +94,150 @@ impl Drawable for TabsWidget {
         f.render_widget(Clear, tab_layout[0]);
         f.render_widget(tabs, tab_layout[0]);
 
-        let content = self.tab_manager.borrow().current().content.clone();
+        let area = tab_layout[1];
+        let height = area.height as usize;
+        self.page_height = height;
+
+        // One column is kept free for the scrollbar
+        let (lines, scroll, max_scroll) = {
+            let mut tab_manager = self.tab_manager.borrow_mut();
+            let tab = tab_manager.current_mut();
+            if tab.is_loading() {
+                (vec![Line::from(format!("Loading {}...", tab.url))], 0, 0)
+            } else {
+                let width = area.width.saturating_sub(1) as usize;
+                tab.update_search(width, height);
+
+                let total = tab.page(width).lines.len();
+                let max_scroll = total.saturating_sub(height);
+                let scroll = tab.scroll.min(max_scroll);
+                tab.scroll = scroll;
+
+                let mut lines =
+                    tab.page(width).lines[scroll..(scroll + height).min(total)].to_vec();
+                if let Some(search) = &tab.search {
+                    lines = lines
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, line)| search.highlight(scroll + idx, line))
+                        .collect();
+                }
+                (lines, tab.scroll, max_scroll)
+            }
+        };
+
+        let percentage = (scroll * 100).checked_div(max_scroll).unwrap_or(100);
+        self.status_bar
+            .borrow_mut()
+            .scroll_percentage(Some(percentage as u16));
+
+        let search_status = self
+            .tab_manager
+            .borrow()
+            .current()
+            .search
+            .as_ref()
+            .map(|search| search.status());
+        self.status_bar.borrow_mut().search_status(search_status);
+
         let block = Block::default().borders(Borders::NONE).on_dark_gray();
 
-        let paragraph = Paragraph::new(content)
-            .block(block)
-            .wrap(Wrap { trim: true });
-        f.render_widget(Clear, tab_layout[1]);
-        f.render_widget(paragraph, tab_layout[1]);
+        let paragraph = Paragraph::new(Text::from(lines)).block(block);
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+
+        if max_scroll > 0 {
+            let mut scrollbar_state = ScrollbarState::new(max_scroll)
+                .viewport_content_length(height)
+                .position(scroll);
+            f.render_stateful_widget(
+                Scrollbar::default()
+                    .orientation(ScrollbarOrientation::VerticalRight)
+                    .begin_symbol(Some("↑"))
+                    .end_symbol(Some("↓")),
+                area,
+                &mut scrollbar_state,
+            );
+        }
+
+        if !self.link_number.is_empty() && area.height > 0 {
+            let prompt = Paragraph::new(format!(
+                " Follow link: {} (ENTER to open, ALT-ENTER to open in new tab) ",
+                self.link_number
+            ))
+            .style(Style::default().bg(Color::Yellow).fg(Color::Black));
+            let prompt_area = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+            f.render_widget(Clear, prompt_area);
+            f.render_widget(prompt, prompt_area);
+        }
     }
 
     fn event_handler(
         &mut self,
-        _queue: &mut CommandQueue,
-        _key: KeyEvent,
+        queue: &mut CommandQueue,
+        key: KeyEvent,
     ) -> anyhow::Result<Option<KeyEvent>> {
-        Ok(None)
+        let no_modifiers = key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT;
+
+        match key.code {
+            // Typing a number selects a link to follow
+            Char(c) if c.is_ascii_digit() && no_modifiers => {
+                if self.link_number.len() < 6 {
+                    self.link_number.push(c);
+                }
+            }
+            KeyCode::Backspace if !self.link_number.is_empty() => {
+                self.link_number.pop();
+            }
+            KeyCode::Esc if !self.link_number.is_empty() => {
+                self.link_number.clear();
+            }
+            // Scrolling through the page
+            KeyCode::Down | Char('j') if no_modifiers => self.scroll_by(1),
+            KeyCode::Up | Char('k') if no_modifiers => self.scroll_by(-1),
+            KeyCode::PageDown | Char(' ') if no_modifiers => {
+                self.scroll_by(self.page_height as isize)
+            }
+            KeyCode::PageUp | Char('b') if no_modifiers => {
+                self.scroll_by(-(self.page_height as isize))
+            }
+            Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_by(self.page_height as isize / 2)
+            }
+            Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_by(-(self.page_height as isize / 2))
+            }
+            KeyCode::Home | Char('g') if no_modifiers => {
+                self.tab_manager.borrow_mut().current_mut().scroll = 0;
+            }
+            KeyCode::End | Char('G') if no_modifiers => {
+                // Clamped to the actual end of the page on the next render
+                self.tab_manager.borrow_mut().current_mut().scroll = usize::MAX;
+            }
+            Char('s') if no_modifiers => {
+                queue.push(Command::SaveContent {
+                    idx: self.tab_manager.borrow().current,
+                });
+            }
+            // Jumping between search matches
+            Char('n') if no_modifiers && self.has_search() => self.goto_match(true),
+            Char('N') if no_modifiers && self.has_search() => self.goto_match(false),
+            KeyCode::Esc if self.has_search() => {
+                self.tab_manager.borrow_mut().current_mut().search = None;
+            }
+            KeyCode::Enter if !self.link_number.is_empty() => {
+                if let Ok(number) = self.link_number.parse::<usize>() {
+                    queue.push(Command::FollowLink {
+                        idx: self.tab_manager.borrow().current,
+                        number,
+                        new_tab: key.modifiers.contains(KeyModifiers::ALT),
+                    });
+                }
+                self.link_number.clear();
+            }
+            _ => return Ok(None),
+        }
+
+        Ok(Some(key))
     }
 }
@@ -0,0 +1,136 @@
+use crate::dive::command_queue::CommandQueue;
+use crate::dive::completion::{suggest, Suggestion};
+use crate::dive::widget_manager::Drawable;
+use crate::dive::widgets::input::{InputSubmitCommand, InputWidget};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState};
+use ratatui::Frame;
+
+/// Maximum number of suggestions shown under the prompt
+const MAX_SUGGESTIONS: usize = 8;
+
+/// URL prompt with a list of suggestions from bookmarks, open tabs and history under it. The
+/// suggestions are updated while typing, and TAB or the arrow keys cycle through them.
+pub struct UrlInputWidget {
+    input: InputWidget,
+    /// Pages that can be suggested
+    candidates: Vec<Suggestion>,
+    /// Suggestions for what has been typed
+    suggestions: Vec<Suggestion>,
+    state: ListState,
+}
+
+impl UrlInputWidget {
+    pub fn new(
+        title: &str,
+        value: &str,
+        command: InputSubmitCommand,
+        candidates: Vec<Suggestion>,
+    ) -> Self {
+        let mut widget = Self {
+            input: InputWidget::new(title, value, 80, command),
+            candidates,
+            suggestions: vec![],
+            state: ListState::default(),
+        };
+        widget.update_suggestions();
+
+        widget
+    }
+
+    fn update_suggestions(&mut self) {
+        self.suggestions = suggest(self.input.value(), &self.candidates, MAX_SUGGESTIONS);
+        self.state.select(None);
+    }
+
+    /// Selects the next (or previous) suggestion, and puts its url in the prompt
+    fn cycle(&mut self, forward: bool) {
+        if self.suggestions.is_empty() {
+            return;
+        }
+
+        let last = self.suggestions.len() - 1;
+        let selected = match (self.state.selected(), forward) {
+            (None, true) => 0,
+            (None, false) => last,
+            (Some(idx), true) if idx < last => idx + 1,
+            (Some(_), true) => 0,
+            (Some(0), false) => last,
+            (Some(idx), false) => idx - 1,
+        };
+        self.state.select(Some(selected));
+        self.input.set_value(&self.suggestions[selected].url);
+    }
+}
+
+impl Drawable for UrlInputWidget {
+    fn on_show(&mut self) {}
+    fn on_hide(&mut self) {}
+
+    fn render(&mut self, f: &mut Frame) {
+        self.input.render(f);
+
+        if self.suggestions.is_empty() {
+            return;
+        }
+
+        let input_area = self.input.area(f.size());
+        let top = input_area.y + input_area.height;
+        let height = (self.suggestions.len() as u16 + 2).min(f.size().height.saturating_sub(top));
+        let area = Rect::new(input_area.x, top, input_area.width, height);
+
+        let items = self
+            .suggestions
+            .iter()
+            .map(|suggestion| {
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{:<10}", format!("[{}]", suggestion.source)),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::raw(" "),
+                ];
+                if !suggestion.title.is_empty() {
+                    spans.push(Span::styled(
+                        format!("{} ", suggestion.title),
+                        Style::default().fg(Color::White),
+                    ));
+                }
+                spans.push(Span::styled(
+                    suggestion.url.clone(),
+                    Style::default().fg(Color::Gray),
+                ));
+                ListItem::new(Line::from(spans))
+            })
+            .collect::<Vec<_>>();
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().bg(Color::Blue))
+            .highlight_style(Style::default().bg(Color::Red).add_modifier(Modifier::BOLD));
+
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+
+    fn event_handler(
+        &mut self,
+        queue: &mut CommandQueue,
+        key: KeyEvent,
+    ) -> anyhow::Result<Option<KeyEvent>> {
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => self.cycle(true),
+            KeyCode::BackTab | KeyCode::Up => self.cycle(false),
+            _ => {
+                let before = self.input.value().to_string();
+                self.input.event_handler(queue, key)?;
+                if self.input.value() != before {
+                    self.update_suggestions();
+                }
+            }
+        }
+
+        Ok(Some(key))
+    }
+}
@@ -0,0 +1,191 @@
+use crate::dive::command_queue::{Command, CommandQueue};
+use crate::dive::tab_manager::TabManager;
+use crate::dive::ui::centered_rect;
+use crate::dive::widget_manager::Drawable;
+use crate::dive::workspace_manager::WorkspaceManager;
+use crossterm::event::KeyCode::Char;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// What the name being typed is for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Editing {
+    New,
+    Rename(usize),
+}
+
+/// Picker to switch between workspaces, and to create, rename and delete them
+pub struct WorkspaceListWidget {
+    workspace_manager: Rc<RefCell<WorkspaceManager>>,
+    /// Holds the tabs of the active workspace
+    tab_manager: Rc<RefCell<TabManager>>,
+    state: ListState,
+    /// Set while a name is being typed
+    editing: Option<Editing>,
+    name: String,
+}
+
+impl WorkspaceListWidget {
+    pub fn new(
+        workspace_manager: Rc<RefCell<WorkspaceManager>>,
+        tab_manager: Rc<RefCell<TabManager>>,
+    ) -> Self {
+        let active = workspace_manager.borrow().active();
+        Self {
+            workspace_manager,
+            tab_manager,
+            state: ListState::default().with_selected(Some(active)),
+            editing: None,
+            name: String::new(),
+        }
+    }
+
+    fn selected(&self) -> usize {
+        let len = self.workspace_manager.borrow().workspaces().len();
+        self.state.selected().unwrap_or(0).min(len - 1)
+    }
+
+    fn select(&mut self, offset: isize) {
+        let len = self.workspace_manager.borrow().workspaces().len();
+        let sel = self.selected().saturating_add_signed(offset).min(len - 1);
+        self.state.select(Some(sel));
+    }
+
+    fn submit(&mut self, queue: &mut CommandQueue) {
+        let name = self.name.trim().to_string();
+        match self.editing.take() {
+            Some(Editing::New) => {
+                let len = self.workspace_manager.borrow().workspaces().len();
+                queue.push(Command::CreateWorkspace { name });
+                // The new workspace ends up at the bottom of the list
+                self.state.select(Some(len));
+            }
+            Some(Editing::Rename(idx)) => queue.push(Command::RenameWorkspace { idx, name }),
+            None => {}
+        }
+        self.name.clear();
+    }
+}
+
+impl Drawable for WorkspaceListWidget {
+    fn on_show(&mut self) {}
+    fn on_hide(&mut self) {}
+
+    fn render(&mut self, f: &mut Frame) {
+        let area = centered_rect(50, 40, f.size());
+        f.render_widget(Clear, area);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+
+        let workspace_manager = self.workspace_manager.borrow();
+        let active = workspace_manager.active();
+        let items = workspace_manager
+            .workspaces()
+            .iter()
+            .enumerate()
+            .map(|(idx, ws)| {
+                let tabs = ws
+                    .tabs()
+                    .map_or_else(|| self.tab_manager.borrow().len(), TabManager::len);
+                let marker = if idx == active { "*" } else { " " };
+                ListItem::new(format!("{} {} ({} tabs)", marker, ws.name, tabs))
+            })
+            .collect::<Vec<_>>();
+        drop(workspace_manager);
+
+        let list = List::new(items)
+            .block(Block::default().title("Workspaces").borders(Borders::ALL))
+            .style(Style::default().fg(Color::White))
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Red)
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+
+        let help = match self.editing {
+            Some(Editing::New) => format!(
+                "New workspace: {}_   (ENTER to create, ESC to cancel)",
+                self.name
+            ),
+            Some(Editing::Rename(_)) => format!(
+                "Rename to: {}_   (ENTER to rename, ESC to cancel)",
+                self.name
+            ),
+            None => "ENTER switch  n new  r rename  DEL delete  ESC close".into(),
+        };
+
+        let selected = self.selected();
+        self.state.select(Some(selected));
+        f.render_stateful_widget(list, rows[0], &mut self.state);
+        f.render_widget(
+            Paragraph::new(help).style(Style::default().bg(Color::Blue).fg(Color::White)),
+            rows[1],
+        );
+    }
+
+    fn event_handler(
+        &mut self,
+        queue: &mut CommandQueue,
+        key: KeyEvent,
+    ) -> anyhow::Result<Option<KeyEvent>> {
+        if self.editing.is_some() {
+            match key.code {
+                KeyCode::Esc => {
+                    self.editing = None;
+                    self.name.clear();
+                }
+                KeyCode::Enter => self.submit(queue),
+                KeyCode::Backspace => {
+                    self.name.pop();
+                }
+                Char(c) => self.name.push(c),
+                _ => {}
+            }
+
+            return Ok(Some(key));
+        }
+
+        match key.code {
+            KeyCode::Esc => {
+                queue.push(Command::DestroyWidget {
+                    id: "workspace_list".into(),
+                });
+            }
+            KeyCode::Down => self.select(1),
+            KeyCode::Up => self.select(-1),
+            KeyCode::Enter => {
+                queue.push(Command::SwitchWorkspace {
+                    idx: self.selected(),
+                });
+                queue.push(Command::DestroyWidget {
+                    id: "workspace_list".into(),
+                });
+            }
+            Char('n') => self.editing = Some(Editing::New),
+            Char('r') => {
+                let idx = self.selected();
+                self.name = self.workspace_manager.borrow().workspaces()[idx]
+                    .name
+                    .clone();
+                self.editing = Some(Editing::Rename(idx));
+            }
+            KeyCode::Delete => {
+                queue.push(Command::DeleteWorkspace {
+                    idx: self.selected(),
+                });
+            }
+            _ => {}
+        }
+
+        Ok(Some(key))
+    }
+}
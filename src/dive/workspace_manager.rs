@@ -0,0 +1,368 @@
+use crate::dive::loader::{Content, LoadError};
+use crate::dive::session::Session;
+use crate::dive::tab_manager::TabManager;
+use std::mem;
+use uuid::Uuid;
+
+/// Name of the workspace we start with when there is no previous session
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// A named set of tabs
+pub struct Workspace {
+    pub name: String,
+    /// Tabs of the workspace. None for the active workspace, whose tabs live in the tab manager
+    /// of the app.
+    tabs: Option<TabManager>,
+}
+
+impl Workspace {
+    /// Returns the tabs of the workspace, when it is not the active one
+    pub fn tabs(&self) -> Option<&TabManager> {
+        self.tabs.as_ref()
+    }
+}
+
+/// Keeps the sets of tabs that are not shown. There is always one active workspace: switching
+/// swaps the tabs of the app's tab manager with the tabs of another workspace, so pages in the
+/// background workspaces stay loaded.
+pub struct WorkspaceManager {
+    workspaces: Vec<Workspace>,
+    active: usize,
+}
+
+impl WorkspaceManager {
+    /// Creates a single, active workspace with the given name
+    pub fn new(name: &str) -> Self {
+        Self {
+            workspaces: vec![Workspace {
+                name: name.into(),
+                tabs: None,
+            }],
+            active: 0,
+        }
+    }
+
+    pub fn workspaces(&self) -> &[Workspace] {
+        &self.workspaces
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.workspaces[self.active].name
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.workspaces.iter().position(|ws| ws.name == name)
+    }
+
+    /// Adds a workspace with a single blank tab. Returns its index, or None when the name is
+    /// empty or already taken.
+    pub fn create(&mut self, name: &str, current: &TabManager) -> Option<usize> {
+        let name = name.trim();
+        if name.is_empty() || self.find(name).is_some() {
+            return None;
+        }
+
+        let mut tabs = current.sibling();
+        tabs.open("New Tab", "gosub://blank");
+        self.workspaces.push(Workspace {
+            name: name.into(),
+            tabs: Some(tabs),
+        });
+
+        log::debug!("Created workspace {}", name);
+        Some(self.workspaces.len() - 1)
+    }
+
+    /// Makes the given workspace the active one, by swapping its tabs into the current tab
+    /// manager
+    pub fn switch(&mut self, idx: usize, current: &mut TabManager) -> bool {
+        if idx >= self.workspaces.len() || idx == self.active {
+            return false;
+        }
+
+        let tabs = self.workspaces[idx]
+            .tabs
+            .take()
+            .expect("inactive workspace without tabs");
+        self.workspaces[self.active].tabs = Some(mem::replace(current, tabs));
+        self.active = idx;
+
+        log::trace!("Switching to workspace: {}", self.active_name());
+        true
+    }
+
+    /// Renames the given workspace. Returns false when the name is empty or already taken.
+    pub fn rename(&mut self, idx: usize, name: &str) -> bool {
+        let name = name.trim();
+        if idx >= self.workspaces.len() || name.is_empty() {
+            return false;
+        }
+        if self.find(name).is_some_and(|found| found != idx) {
+            return false;
+        }
+
+        self.workspaces[idx].name = name.into();
+        true
+    }
+
+    /// Removes the given workspace and closes its tabs. The last workspace cannot be deleted;
+    /// deleting the active one switches to its neighbour first.
+    pub fn delete(&mut self, idx: usize, current: &mut TabManager) -> bool {
+        if idx >= self.workspaces.len() || self.workspaces.len() == 1 {
+            return false;
+        }
+
+        if idx == self.active {
+            let neighbour = if idx > 0 { idx - 1 } else { idx + 1 };
+            self.switch(neighbour, current);
+        }
+
+        let workspace = self.workspaces.remove(idx);
+        if self.active > idx {
+            self.active -= 1;
+        }
+
+        log::debug!("Deleted workspace {}", workspace.name);
+        true
+    }
+
+    /// Stores the result of a load started by a tab in a background workspace. Returns the
+    /// index of the workspace and the tab, or None when no background tab waits for the load.
+    pub fn finish_load(
+        &mut self,
+        load_id: Uuid,
+        result: Result<Content, LoadError>,
+    ) -> Option<(usize, usize)> {
+        let (idx, tabs) = self
+            .workspaces
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(idx, ws)| Some((idx, ws.tabs.as_mut()?)))
+            .find(|(_, tabs)| tabs.loading_tab(load_id).is_some())?;
+
+        let tab_idx = tabs.finish_load(load_id, result)?;
+        Some((idx, tab_idx))
+    }
+
+    /// Returns the tabs of all workspaces, so they can be restored on the next start
+    pub fn session(&self, current: &TabManager) -> Session {
+        let workspaces = self
+            .workspaces
+            .iter()
+            .map(|ws| ws.tabs.as_ref().unwrap_or(current).session(&ws.name))
+            .collect();
+
+        Session {
+            workspaces,
+            active: self.active,
+            ..Session::default()
+        }
+    }
+
+    /// Replaces the workspaces with the ones of a saved session, and opens their tabs. The tabs
+    /// of the active workspace are opened in the current tab manager. Background workspaces
+    /// without any valid tabs get a blank tab. Returns the number of tabs that have been
+    /// restored, or None when the session has no workspaces at all.
+    pub fn restore(&mut self, session: &Session, current: &mut TabManager) -> Option<usize> {
+        if session.workspaces.is_empty() {
+            return None;
+        }
+
+        let active = session.active.min(session.workspaces.len() - 1);
+        let mut restored = 0;
+        let mut workspaces = vec![];
+        for (idx, ws) in session.workspaces.iter().enumerate() {
+            // Names have to be unique, so we can find workspaces by name
+            let base = match ws.name.trim() {
+                "" => DEFAULT_WORKSPACE,
+                name => name,
+            };
+            let mut name = base.to_string();
+            let mut count = 1;
+            while workspaces.iter().any(|w: &Workspace| w.name == name) {
+                count += 1;
+                name = format!("{} ({})", base, count);
+            }
+
+            if idx == active {
+                restored += current.restore(ws);
+                workspaces.push(Workspace { name, tabs: None });
+                continue;
+            }
+
+            let mut tabs = current.sibling();
+            restored += tabs.restore(ws);
+            if tabs.len() == 0 {
+                tabs.open("New Tab", "gosub://blank");
+            }
+            workspaces.push(Workspace {
+                name,
+                tabs: Some(tabs),
+            });
+        }
+
+        self.workspaces = workspaces;
+        self.active = active;
+        Some(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dive::cache::HttpCache;
+    use crate::dive::command_queue::Command;
+    use crate::dive::cookies::CookieJar;
+    use crate::dive::fetcher::mock::MockFetcher;
+    use crate::dive::loader::LoadContext;
+    use crate::dive::session::{TabSession, WorkspaceSession};
+    use crate::dive::settings::Settings;
+    use crate::dive::tab_manager::HistoryEntry;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn tab_manager(fetcher: MockFetcher) -> (TabManager, Receiver<Command>) {
+        let (sender, receiver) = channel();
+        let context = LoadContext {
+            settings: Settings::default(),
+            fetcher: Arc::new(fetcher),
+            cookies: Arc::new(Mutex::new(CookieJar::in_memory())),
+            cache: Arc::new(Mutex::new(HttpCache::disabled())),
+        };
+
+        (TabManager::new(sender, context), receiver)
+    }
+
+    fn urls(tabs: &TabManager) -> Vec<&str> {
+        tabs.tabs.iter().map(|tab| tab.url.as_str()).collect()
+    }
+
+    fn workspace(name: &str, urls: &[&str]) -> WorkspaceSession {
+        let tabs = urls
+            .iter()
+            .map(|url| TabSession {
+                name: url.to_string(),
+                history: vec![HistoryEntry {
+                    url: url.to_string(),
+                    scroll: 0,
+                }],
+                history_idx: 0,
+                scroll: 0,
+            })
+            .collect();
+
+        WorkspaceSession {
+            name: name.into(),
+            tabs,
+            current: 0,
+        }
+    }
+
+    #[test]
+    fn switches_between_sets_of_tabs() {
+        let (mut current, _receiver) = tab_manager(MockFetcher::new());
+        current.open("Docs", "https://docs.rs/");
+        let mut workspaces = WorkspaceManager::new("docs");
+
+        assert_eq!(workspaces.create("oncall", &current), Some(1));
+        assert_eq!(workspaces.create("oncall", &current), None);
+        assert_eq!(workspaces.create(" ", &current), None);
+
+        assert!(workspaces.switch(1, &mut current));
+        assert_eq!(workspaces.active_name(), "oncall");
+        assert_eq!(urls(&current), vec!["gosub://blank"]);
+        assert_eq!(
+            workspaces.workspaces()[0].tabs().map(TabManager::len),
+            Some(1)
+        );
+
+        assert!(workspaces.switch(0, &mut current));
+        assert!(!workspaces.switch(0, &mut current));
+        assert_eq!(urls(&current), vec!["https://docs.rs/"]);
+    }
+
+    #[test]
+    fn renames_and_deletes_workspaces() {
+        let (mut current, _receiver) = tab_manager(MockFetcher::new());
+        current.open("Docs", "https://docs.rs/");
+        let mut workspaces = WorkspaceManager::new("docs");
+        workspaces.create("oncall", &current);
+        workspaces.create("research", &current);
+
+        assert!(!workspaces.rename(1, "docs"));
+        assert!(workspaces.rename(1, "pager"));
+        assert_eq!(workspaces.find("pager"), Some(1));
+
+        // Deleting the active workspace shows its neighbour
+        assert!(workspaces.delete(0, &mut current));
+        assert_eq!(workspaces.active_name(), "pager");
+        assert_eq!(urls(&current), vec!["gosub://blank"]);
+
+        assert!(workspaces.delete(1, &mut current));
+        assert!(!workspaces.delete(0, &mut current));
+        assert_eq!(workspaces.workspaces().len(), 1);
+    }
+
+    #[test]
+    fn finishes_loads_in_background_workspaces() {
+        let fetcher = MockFetcher::new()
+            .page("https://example.com/", "text/html", "Example")
+            .delay("https://example.com/", Duration::from_millis(50));
+        let (mut current, receiver) = tab_manager(fetcher);
+        current.open("Example", "https://example.com/");
+        let mut workspaces = WorkspaceManager::new("docs");
+        workspaces.create("oncall", &current);
+        workspaces.switch(1, &mut current);
+
+        loop {
+            match receiver.recv_timeout(Duration::from_secs(5)) {
+                Ok(Command::ContentLoaded { load_id, result }) => {
+                    if current.loading_tab(load_id).is_none() {
+                        assert_eq!(workspaces.finish_load(load_id, result), Some((0, 0)));
+                        break;
+                    }
+                    current.finish_load(load_id, result);
+                }
+                other => panic!("expected a finished load, got {:?}", other),
+            }
+        }
+
+        workspaces.switch(0, &mut current);
+        assert_eq!(current.tabs[0].content.as_text(), Some("Example"));
+    }
+
+    #[test]
+    fn saves_and_restores_all_workspaces() {
+        let (mut current, _receiver) = tab_manager(MockFetcher::new());
+        let session = Session {
+            workspaces: vec![
+                workspace("docs", &["https://docs.rs/", "https://doc.rust-lang.org/"]),
+                workspace("oncall", &[]),
+                workspace("docs", &["https://example.com/"]),
+            ],
+            active: 2,
+            ..Session::default()
+        };
+
+        let mut workspaces = WorkspaceManager::new(DEFAULT_WORKSPACE);
+        assert_eq!(workspaces.restore(&session, &mut current), Some(3));
+        assert_eq!(workspaces.active_name(), "docs (2)");
+        assert_eq!(urls(&current), vec!["https://example.com/"]);
+        assert_eq!(
+            workspaces.workspaces()[1].tabs().map(TabManager::len),
+            Some(1)
+        );
+
+        let saved = workspaces.session(&current);
+        assert_eq!(saved.active, 2);
+        assert_eq!(saved.workspaces[0], session.workspaces[0]);
+        assert_eq!(saved.workspaces[2].tabs, session.workspaces[2].tabs);
+
+        assert_eq!(workspaces.restore(&Session::default(), &mut current), None);
+    }
+}
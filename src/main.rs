@@ -39,6 +39,7 @@ fn run(app: &mut App) -> Result<()> {
 
         app.handle_events()?;
         app.process_commands();
+        app.autosave();
 
         if app.should_quit {
             break;
@@ -73,16 +74,15 @@ fn main() -> Result<()> {
 
     let mut app = App::new(log_pool);
 
-    app.tab_manager
-        .borrow_mut()
-        .open("New Tab", "gosub://blank");
+    // Start with the tabs of the previous session, unless asked not to
+    let restore = !std::env::args().any(|arg| arg == "--no-restore");
+    if !(restore && app.restore_session()) {
+        app.tab_manager
+            .borrow_mut()
+            .open("New Tab", "gosub://blank");
+    }
 
-    let w1 = Widget::new(
-        "splash",
-        255,
-        false,
-        Rc::new(RefCell::new(SplashWidget::new())),
-    );
+    let w1 = Widget::new("splash", false, Rc::new(RefCell::new(SplashWidget::new())));
     app.widget_manager.create(w1);
     app.widget_manager.show("splash", true);
 